@@ -0,0 +1,151 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize)]
+struct CheckpointFile {
+    file: String,
+    watermark: u64,
+}
+
+/// Tracks the contiguous line-offset watermark that has been fully, durably uploaded. Batches
+/// complete out of order, so a range that finishes ahead of the watermark is held in `pending`
+/// until the gap in front of it closes, then the watermark jumps forward and is persisted.
+pub struct CheckpointTracker {
+    path: Option<String>,
+    source_file: String,
+    watermark: u64,
+    pending: BTreeMap<u64, u64>,
+}
+
+impl CheckpointTracker {
+    /// Line offset to resume from: 0 if there's no checkpoint at `path`, or if it was written
+    /// for a different input file.
+    pub fn resume_offset(path: &str, source_file: &str) -> u64 {
+        let Ok(contents) = std::fs::read_to_string(path) else {
+            return 0;
+        };
+        match serde_json::from_str::<CheckpointFile>(&contents) {
+            Ok(checkpoint) if checkpoint.file == source_file => checkpoint.watermark,
+            _ => 0,
+        }
+    }
+
+    pub fn new(path: Option<String>, source_file: String, watermark: u64) -> Self {
+        Self {
+            path,
+            source_file,
+            watermark,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Record that lines `[start, end)` finished uploading. Advances and persists the watermark
+    /// if this closes the gap, otherwise just remembers the range for later.
+    pub fn complete_range(&mut self, start: u64, end: u64) -> Result<()> {
+        if self.path.is_none() {
+            return Ok(());
+        }
+
+        self.pending.insert(start, end);
+
+        let mut advanced = false;
+        while let Some(end) = self.pending.remove(&self.watermark) {
+            self.watermark = end;
+            advanced = true;
+        }
+
+        if advanced {
+            self.persist()?;
+        }
+        Ok(())
+    }
+
+    fn persist(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let checkpoint = CheckpointFile {
+            file: self.source_file.clone(),
+            watermark: self.watermark,
+        };
+        let contents =
+            serde_json::to_string(&checkpoint).context("Failed to serialize checkpoint")?;
+        std::fs::write(path, contents).context("Failed to write checkpoint file")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker_at(path: &str, watermark: u64) -> CheckpointTracker {
+        CheckpointTracker::new(Some(path.to_string()), "source.log".to_string(), watermark)
+    }
+
+    #[test]
+    fn test_complete_range_holds_out_of_order_ranges() {
+        let path = std::env::temp_dir().join(format!(
+            "os_bulk_index_test_checkpoint_{}_holds.json",
+            std::process::id()
+        ));
+        let mut tracker = tracker_at(path.to_str().unwrap(), 0);
+
+        // Batch [100, 200) finishes before [0, 100): the watermark can't jump over the gap yet.
+        tracker.complete_range(100, 200).expect("complete_range failed");
+        assert_eq!(tracker.watermark, 0);
+        assert!(!path.exists(), "watermark didn't advance, so nothing should be persisted yet");
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_complete_range_advances_watermark_once_gap_closes() {
+        let path = std::env::temp_dir().join(format!(
+            "os_bulk_index_test_checkpoint_{}_advances.json",
+            std::process::id()
+        ));
+        let mut tracker = tracker_at(path.to_str().unwrap(), 0);
+
+        tracker.complete_range(100, 200).expect("complete_range failed");
+        tracker.complete_range(0, 100).expect("complete_range failed");
+
+        // [0, 100) closes the gap in front of [100, 200), so the watermark should jump straight
+        // to 200 rather than stopping at 100.
+        assert_eq!(tracker.watermark, 200);
+
+        let persisted = std::fs::read_to_string(&path).expect("checkpoint file wasn't written");
+        let checkpoint: CheckpointFile =
+            serde_json::from_str(&persisted).expect("checkpoint file wasn't valid JSON");
+        assert_eq!(checkpoint.watermark, 200);
+        assert_eq!(checkpoint.file, "source.log");
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_complete_range_without_path_tracks_nothing() {
+        let mut tracker = CheckpointTracker::new(None, "source.log".to_string(), 0);
+        tracker.complete_range(0, 100).expect("complete_range failed");
+        assert_eq!(tracker.watermark, 0);
+    }
+
+    #[test]
+    fn test_resume_offset_defaults_to_zero_for_missing_or_mismatched_file() {
+        assert_eq!(CheckpointTracker::resume_offset("/no/such/path.json", "source.log"), 0);
+
+        let path = std::env::temp_dir().join(format!(
+            "os_bulk_index_test_checkpoint_{}_resume.json",
+            std::process::id()
+        ));
+        std::fs::write(&path, r#"{"file":"other.log","watermark":500}"#)
+            .expect("Failed to write test checkpoint file");
+        assert_eq!(
+            CheckpointTracker::resume_offset(path.to_str().unwrap(), "source.log"),
+            0
+        );
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,284 @@
+use anyhow::{Context, Result};
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
+use rdkafka::message::Message;
+use rdkafka::topic_partition_list::TopicPartitionList;
+use reqwest::Client;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, Semaphore, mpsc};
+use tokio::time::Instant;
+
+use crate::progress::ProgressEvent;
+use crate::{Cli, bulk_entry_size, replace_timestamps, spawn_upload_task};
+
+/// Continuously consume `args.kafka_topic`, batching record values through the same bulk
+/// pipeline as `process_file` (always in live mode, since a Kafka record has no stable line
+/// offset to derive a deterministic `_id` from), flushing early on `--linger-ms` so a slow
+/// topic is still indexed promptly instead of stalling on `--batch-size`. Batches also flush on
+/// `--max-batch-bytes`, the same byte accounting `process_file` does via `bulk_entry_size`, so a
+/// burst of large records can't build a `_bulk` body past `http.max_content_length` just because
+/// it arrived under the record-count threshold.
+///
+/// Offsets are committed only after the batch they belong to is confirmed uploaded, and batches
+/// are processed one at a time rather than the concurrent pipeline file mode uses, so a crash
+/// mid-batch simply re-delivers the same records on restart instead of needing a watermark to
+/// reconcile out-of-order completions across partitions.
+pub(crate) async fn run(
+    args: &Cli,
+    progress_tx: mpsc::UnboundedSender<ProgressEvent>,
+    client: Client,
+    semaphore: Arc<Semaphore>,
+    dead_letter: Option<Arc<Mutex<tokio::fs::File>>>,
+) -> Result<()> {
+    let brokers = args
+        .kafka_brokers
+        .as_deref()
+        .context("--kafka-brokers is required for --source kafka")?;
+    let topic = args
+        .kafka_topic
+        .as_deref()
+        .context("--kafka-topic is required for --source kafka")?;
+
+    let consumer: StreamConsumer = ClientConfig::new()
+        .set("bootstrap.servers", brokers)
+        .set("group.id", &args.kafka_group)
+        .set("enable.auto.commit", "false")
+        .set("auto.offset.reset", "earliest")
+        .create()
+        .context("Failed to create Kafka consumer")?;
+    consumer
+        .subscribe(&[topic])
+        .context("Failed to subscribe to Kafka topic")?;
+
+    let linger = Duration::from_millis(args.linger_ms);
+    let mut current_batch = Vec::with_capacity(args.batch_size);
+    let mut current_batch_bytes = 0usize;
+    // Highest offset seen per partition in the current batch, to commit once it succeeds.
+    let mut current_offsets: HashMap<i32, i64> = HashMap::new();
+    // Set once, when the first message of a new batch is buffered, so the linger window is
+    // fixed relative to that message rather than being pushed back by every later arrival (a
+    // fresh `sleep(linger)` racing `select!` each iteration would measure the gap since the
+    // *last* event, not elapsed time since the batch started, and could delay a flush
+    // indefinitely under steady traffic faster than `linger_ms`).
+    let mut batch_deadline: Option<Instant> = None;
+
+    loop {
+        let sleep = tokio::time::sleep_until(
+            batch_deadline.unwrap_or_else(|| Instant::now() + Duration::from_secs(3600)),
+        );
+
+        tokio::select! {
+            message = consumer.recv() => {
+                let message = message.context("Kafka consumer error")?;
+                let payload = message
+                    .payload()
+                    .map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+                    .unwrap_or_default();
+                // Always live (see `run`'s doc comment), so replace the timestamp here, once,
+                // rather than leaving it for `create_bulk_body` to redo on every flush.
+                let payload = replace_timestamps(&payload);
+                let payload_bytes = bulk_entry_size(&payload, &args.index, true);
+                let partition = message.partition();
+                let offset = message.offset();
+
+                // A single record already over the limit can't be combined with anything else;
+                // flush whatever's pending, then send it alone rather than dropping it or
+                // looping forever trying to make room (mirrors `process_file`).
+                if payload_bytes > args.max_batch_bytes {
+                    eprintln!(
+                        "Warning: record exceeds --max-batch-bytes ({} > {}); sending as its own batch",
+                        payload_bytes, args.max_batch_bytes
+                    );
+                    if !current_batch.is_empty() {
+                        flush_and_commit(
+                            &mut current_batch,
+                            &mut current_batch_bytes,
+                            &mut current_offsets,
+                            args,
+                            topic,
+                            &progress_tx,
+                            &client,
+                            &semaphore,
+                            &dead_letter,
+                            &consumer,
+                        )
+                        .await?;
+                    }
+                    record_offset(&mut current_offsets, partition, offset);
+                    current_batch.push(payload);
+                    current_batch_bytes += payload_bytes;
+                    let _ = progress_tx.send(ProgressEvent::LineRead);
+                    flush_and_commit(
+                        &mut current_batch,
+                        &mut current_batch_bytes,
+                        &mut current_offsets,
+                        args,
+                        topic,
+                        &progress_tx,
+                        &client,
+                        &semaphore,
+                        &dead_letter,
+                        &consumer,
+                    )
+                    .await?;
+                    batch_deadline = None;
+                    continue;
+                }
+
+                if !current_batch.is_empty() && current_batch_bytes + payload_bytes > args.max_batch_bytes {
+                    flush_and_commit(
+                        &mut current_batch,
+                        &mut current_batch_bytes,
+                        &mut current_offsets,
+                        args,
+                        topic,
+                        &progress_tx,
+                        &client,
+                        &semaphore,
+                        &dead_letter,
+                        &consumer,
+                    )
+                    .await?;
+                    batch_deadline = None;
+                }
+
+                record_offset(&mut current_offsets, partition, offset);
+                if current_batch.is_empty() {
+                    batch_deadline = Some(Instant::now() + linger);
+                }
+                current_batch.push(payload);
+                current_batch_bytes += payload_bytes;
+                let _ = progress_tx.send(ProgressEvent::LineRead);
+
+                if current_batch.len() >= args.batch_size || current_batch_bytes >= args.max_batch_bytes {
+                    flush_and_commit(
+                        &mut current_batch,
+                        &mut current_batch_bytes,
+                        &mut current_offsets,
+                        args,
+                        topic,
+                        &progress_tx,
+                        &client,
+                        &semaphore,
+                        &dead_letter,
+                        &consumer,
+                    )
+                    .await?;
+                    batch_deadline = None;
+                }
+            }
+            _ = sleep, if batch_deadline.is_some() => {
+                flush_and_commit(
+                    &mut current_batch,
+                    &mut current_batch_bytes,
+                    &mut current_offsets,
+                    args,
+                    topic,
+                    &progress_tx,
+                    &client,
+                    &semaphore,
+                    &dead_letter,
+                    &consumer,
+                )
+                .await?;
+                batch_deadline = None;
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn flush_and_commit(
+    current_batch: &mut Vec<String>,
+    current_batch_bytes: &mut usize,
+    current_offsets: &mut HashMap<i32, i64>,
+    args: &Cli,
+    topic: &str,
+    progress_tx: &mpsc::UnboundedSender<ProgressEvent>,
+    client: &Client,
+    semaphore: &Arc<Semaphore>,
+    dead_letter: &Option<Arc<Mutex<tokio::fs::File>>>,
+    consumer: &StreamConsumer,
+) -> Result<()> {
+    let batch = std::mem::replace(current_batch, Vec::with_capacity(args.batch_size));
+    *current_batch_bytes = 0;
+    let offsets = std::mem::take(current_offsets);
+
+    let _ = progress_tx.send(ProgressEvent::BatchSubmitted);
+    let handle = spawn_upload_task(
+        batch,
+        Arc::clone(semaphore),
+        client.clone(),
+        progress_tx.clone(),
+        &args.endpoint,
+        &args.index,
+        args.username.as_deref(),
+        args.password.as_deref(),
+        true, // always live: Kafka records have no stable line offset for a deterministic _id
+        dead_letter.clone(),
+        false, // incremental dedup needs the deterministic _id scheme, unavailable in live mode
+    );
+
+    // A batch isn't "done" until the task returned by `spawn_upload_task` resolves, so we simply
+    // await it here instead of threading it through a pending queue. That keeps offset commits in-order per partition without needing a watermark.
+    handle
+        .await
+        .context("Kafka upload task panicked")?
+        .context("Kafka upload task failed")?;
+
+    let mut tpl = TopicPartitionList::new();
+    for (partition, offset) in commit_targets(offsets) {
+        tpl.add_partition_offset(topic, partition, rdkafka::Offset::Offset(offset))
+            .context("Failed to build offset commit list")?;
+    }
+    consumer
+        .commit(&tpl, CommitMode::Sync)
+        .context("Failed to commit Kafka offsets")?;
+
+    Ok(())
+}
+
+/// Record the latest offset seen for `partition` in the in-flight batch. Last offset wins: a
+/// partition can show up many times before a batch flushes, and only the highest offset matters
+/// since committing it implies every earlier offset on that partition was consumed too.
+fn record_offset(current_offsets: &mut HashMap<i32, i64>, partition: i32, offset: i64) {
+    current_offsets.insert(partition, offset);
+}
+
+/// Convert per-partition high-water-mark offsets into the `(partition, offset)` pairs to commit.
+/// Kafka's commit semantics are "resume after this offset", so committing the highest offset
+/// actually read (`N`) means the next read must start at `N + 1`.
+fn commit_targets(offsets: HashMap<i32, i64>) -> Vec<(i32, i64)> {
+    offsets
+        .into_iter()
+        .map(|(partition, offset)| (partition, offset + 1))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_offset_keeps_the_latest_value_per_partition() {
+        let mut offsets = HashMap::new();
+        record_offset(&mut offsets, 0, 5);
+        record_offset(&mut offsets, 0, 9);
+        record_offset(&mut offsets, 1, 2);
+        assert_eq!(offsets.get(&0), Some(&9));
+        assert_eq!(offsets.get(&1), Some(&2));
+    }
+
+    #[test]
+    fn test_commit_targets_commits_one_past_the_highest_offset_seen() {
+        let mut offsets = HashMap::new();
+        offsets.insert(0, 9);
+        offsets.insert(1, 2);
+
+        let mut targets = commit_targets(offsets);
+        targets.sort();
+        assert_eq!(targets, vec![(0, 10), (1, 3)]);
+    }
+}
@@ -1,25 +1,62 @@
+mod checkpoint;
 mod file_reader;
+mod kafka_source;
 mod progress;
 
 use anyhow::{Context, Result};
 use chrono::Utc;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use regex::Regex;
 use reqwest::Client;
+use serde::Deserialize;
 use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::sync::{Arc, OnceLock};
-use tokio::sync::{Semaphore, mpsc};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{Mutex, Semaphore, mpsc};
+use tokio_stream::StreamExt;
 
-use file_reader::create_reader;
+use checkpoint::CheckpointTracker;
+use file_reader::{create_reader, input_size};
 use progress::{ProgressEvent, handle_progress_events};
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum Source {
+    /// Read once from `--file` (or stdin)
+    File,
+    /// Continuously consume records from a Kafka topic
+    Kafka,
+}
+
 #[derive(Parser, Debug)]
 #[command(name = "os-bulk-index")]
 #[command(about = "Bulk index documents into OpenSearch/Elasticsearch")]
 struct Cli {
-    /// Path to the dataset file (supports .json, .json.gz, .json.zst)
+    /// Where to read documents from
+    #[arg(long, value_enum, default_value_t = Source::File)]
+    source: Source,
+
+    /// Path, or http(s):// URL, to the dataset file (supports .json, .json.gz, .json.bz2,
+    /// .json.zst, .json.xz, .json.lz4). Required for `--source file`
     #[arg(short, long)]
-    file: String,
+    file: Option<String>,
+
+    /// Kafka broker list (e.g. "localhost:9092"). Required for `--source kafka`
+    #[arg(long)]
+    kafka_brokers: Option<String>,
+
+    /// Kafka topic to consume. Required for `--source kafka`
+    #[arg(long)]
+    kafka_topic: Option<String>,
+
+    /// Kafka consumer group id, used with `--source kafka`
+    #[arg(long, default_value = "os-bulk-index")]
+    kafka_group: String,
+
+    /// Flush a partial batch after this many milliseconds even if `batch_size` hasn't been hit,
+    /// so a slow-moving Kafka topic still gets indexed promptly
+    #[arg(long, default_value_t = 1000)]
+    linger_ms: u64,
 
     /// Target index name
     #[arg(short, long)]
@@ -45,6 +82,10 @@ struct Cli {
     #[arg(short, long, default_value_t = 8192)]
     batch_size: usize,
 
+    /// Maximum accumulated body size (bytes) per batch, to stay under http.max_content_length
+    #[arg(long, default_value_t = 50_000_000)]
+    max_batch_bytes: usize,
+
     /// Maximum number of concurrent requests
     #[arg(short, long, default_value_t = 32)]
     concurrent_requests: usize,
@@ -56,6 +97,20 @@ struct Cli {
     /// Live mode: skip _id field and replace timestamps with current time
     #[arg(long)]
     live: bool,
+
+    /// Append permanently-failed source lines and their error reason to this file
+    #[arg(long)]
+    dead_letter_file: Option<String>,
+
+    /// Before uploading each batch, skip documents whose deterministic _id already exists in
+    /// the index (via _mget), so a rerun against a partially-loaded index only sends the rest
+    #[arg(long)]
+    incremental: bool,
+
+    /// Track fully-uploaded line ranges in this file so an interrupted load can resume instead
+    /// of restarting from the beginning
+    #[arg(long)]
+    checkpoint: Option<String>,
 }
 
 #[tokio::main]
@@ -66,11 +121,33 @@ async fn main() -> Result<()> {
     let semaphore = Arc::new(Semaphore::new(args.concurrent_requests));
     let (progress_tx, progress_rx) = mpsc::unbounded_channel();
 
-    let progress_handle = tokio::spawn(handle_progress_events(progress_rx, args.limit));
-    let result = process_file(&args, progress_tx, client, semaphore).await;
+    let dead_letter = match &args.dead_letter_file {
+        Some(path) => Some(Arc::new(Mutex::new(
+            tokio::fs::File::create(path)
+                .await
+                .context("Failed to create dead-letter file")?,
+        ))),
+        None => None,
+    };
+
+    let total_bytes = match args.source {
+        Source::File => input_size(args.file.as_deref()),
+        Source::Kafka => None,
+    };
+
+    let progress_handle = tokio::spawn(handle_progress_events(progress_rx, args.limit, total_bytes));
+    let result = match args.source {
+        Source::File => {
+            if args.file.is_none() {
+                anyhow::bail!("--file is required for --source file");
+            }
+            process_file(&args, progress_tx, client, semaphore, dead_letter).await
+        }
+        Source::Kafka => kafka_source::run(&args, progress_tx, client, semaphore, dead_letter).await,
+    };
 
     progress_handle.await.context("Progress task panicked")??;
-    result.context("Failed to process file")
+    result.context("Failed to process input")
 }
 
 async fn process_file(
@@ -78,44 +155,200 @@ async fn process_file(
     progress_tx: mpsc::UnboundedSender<ProgressEvent>,
     client: Client,
     semaphore: Arc<Semaphore>,
+    dead_letter: Option<Arc<Mutex<tokio::fs::File>>>,
 ) -> Result<()> {
-    let reader = create_reader(&args.file)?;
+    let file = args.file.as_deref().expect("--file is required for --source file");
+
+    let resume_offset = args
+        .checkpoint
+        .as_deref()
+        .map(|path| CheckpointTracker::resume_offset(path, file))
+        .unwrap_or(0);
+    if resume_offset > 0 {
+        eprintln!("Resuming from checkpoint at line {resume_offset}");
+    }
+    let mut tracker = CheckpointTracker::new(args.checkpoint.clone(), file.to_string(), resume_offset);
+
+    let reader = create_reader(Some(file), progress_tx.clone()).await?;
     let mut current_batch = Vec::with_capacity(args.batch_size);
+    let mut current_batch_bytes = 0usize;
+    let mut batch_start_line = resume_offset;
+    let mut line_index = resume_offset;
     let mut pending_handles = Vec::new();
 
-    for line in reader.lines().take(args.limit.unwrap_or(usize::MAX)) {
+    let lines_stream = reader
+        .lines_stream()
+        .skip(resume_offset as usize)
+        .take(args.limit.unwrap_or(usize::MAX));
+    tokio::pin!(lines_stream);
+
+    while let Some(line) = lines_stream.next().await {
         let line = line.context("Failed to read line")?;
-        current_batch.push(line);
+        // Compute the timestamp-replaced line once here (live mode only; the line is used
+        // unchanged otherwise) and reuse it for both the size check and the eventual bulk body, instead
+        // of re-running the timestamp regex over the line a second time in `create_bulk_body`.
+        let prepared_line = if args.live { replace_timestamps(&line) } else { line };
+        let line_bytes = bulk_entry_size(&prepared_line, &args.index, args.live);
+
+        // A single line that's already over the limit can't be combined with anything else;
+        // send it alone rather than dropping it or looping forever trying to make room.
+        if line_bytes > args.max_batch_bytes {
+            eprintln!(
+                "Warning: line exceeds --max-batch-bytes ({} > {}); sending as its own batch",
+                line_bytes, args.max_batch_bytes
+            );
+            if !current_batch.is_empty() {
+                flush_batch(
+                    &mut current_batch,
+                    &mut current_batch_bytes,
+                    batch_start_line,
+                    line_index,
+                    args,
+                    &progress_tx,
+                    &client,
+                    &semaphore,
+                    &mut pending_handles,
+                    &dead_letter,
+                    &mut tracker,
+                )
+                .await?;
+            }
+            current_batch.push(prepared_line);
+            line_index += 1;
+            flush_batch(
+                &mut current_batch,
+                &mut current_batch_bytes,
+                line_index - 1,
+                line_index,
+                args,
+                &progress_tx,
+                &client,
+                &semaphore,
+                &mut pending_handles,
+                &dead_letter,
+                &mut tracker,
+            )
+            .await?;
+            batch_start_line = line_index;
+            let _ = progress_tx.send(ProgressEvent::LineRead);
+            continue;
+        }
+
+        if !current_batch.is_empty() && current_batch_bytes + line_bytes > args.max_batch_bytes {
+            flush_batch(
+                &mut current_batch,
+                &mut current_batch_bytes,
+                batch_start_line,
+                line_index,
+                args,
+                &progress_tx,
+                &client,
+                &semaphore,
+                &mut pending_handles,
+                &dead_letter,
+                &mut tracker,
+            )
+            .await?;
+            batch_start_line = line_index;
+        }
+
+        current_batch_bytes += line_bytes;
+        current_batch.push(prepared_line);
+        line_index += 1;
         let _ = progress_tx.send(ProgressEvent::LineRead);
 
         if current_batch.len() >= args.batch_size {
-            let batch = std::mem::replace(&mut current_batch, Vec::with_capacity(args.batch_size));
-            let _ = progress_tx.send(ProgressEvent::BatchSubmitted);
-            pending_handles.push(spawn_upload_task(
-                batch,
-                Arc::clone(&semaphore),
-                client.clone(),
-                progress_tx.clone(),
-                &args.endpoint,
-                &args.index,
-                args.username.as_deref(),
-                args.password.as_deref(),
-                args.live,
-            ));
-
-            // When we hit our limit, start going through the queue
-            while pending_handles.len() >= args.max_pending_batches {
-                remove_completed(&mut pending_handles).await?;
-            }
+            flush_batch(
+                &mut current_batch,
+                &mut current_batch_bytes,
+                batch_start_line,
+                line_index,
+                args,
+                &progress_tx,
+                &client,
+                &semaphore,
+                &mut pending_handles,
+                &dead_letter,
+                &mut tracker,
+            )
+            .await?;
+            batch_start_line = line_index;
         }
     }
 
     // Handle remaining documents
     if !current_batch.is_empty() {
-        let _ = progress_tx.send(ProgressEvent::BatchSubmitted);
-        pending_handles.push(spawn_upload_task(
-            current_batch,
-            Arc::clone(&semaphore),
+        flush_batch(
+            &mut current_batch,
+            &mut current_batch_bytes,
+            batch_start_line,
+            line_index,
+            args,
+            &progress_tx,
+            &client,
+            &semaphore,
+            &mut pending_handles,
+            &dead_letter,
+            &mut tracker,
+        )
+        .await?;
+    }
+
+    // Leftover tasks
+    while !pending_handles.is_empty() {
+        remove_completed(&mut pending_handles, &mut tracker).await?;
+    }
+
+    let _ = progress_tx.send(ProgressEvent::Finished);
+
+    Ok(())
+}
+
+/// Byte size of the `{"create":{...}}` action line plus `prepared_line` and its trailing
+/// newline, i.e. exactly what `create_bulk_body` will append to the batch body for this line.
+/// `prepared_line` must already be the line as `create_bulk_body` will write it (timestamp
+/// regex applied in live mode), since the caller computes it once and passes it to both
+/// functions rather than running the regex here a second time.
+pub(crate) fn bulk_entry_size(prepared_line: &str, index: &str, live: bool) -> usize {
+    let action_line = if live {
+        format!("{{\"create\":{{\"_index\":\"{}\"}}}}\n", index)
+    } else {
+        // The _id is a fixed-width 24-char hex digest, so its length doesn't depend on the line.
+        format!(
+            "{{\"create\":{{\"_index\":\"{}\",\"_id\":\"{}\"}}}}\n",
+            index,
+            "0".repeat(24)
+        )
+    };
+    action_line.len() + prepared_line.len() + 1
+}
+
+/// Submit the current batch for upload and reset the accumulator, queuing up behind
+/// `max_pending_batches` if the upload queue is already full. `start_line`/`end_line` is the
+/// half-open range of input line offsets this batch covers, for checkpointing.
+#[allow(clippy::too_many_arguments)]
+async fn flush_batch(
+    current_batch: &mut Vec<String>,
+    current_batch_bytes: &mut usize,
+    start_line: u64,
+    end_line: u64,
+    args: &Cli,
+    progress_tx: &mpsc::UnboundedSender<ProgressEvent>,
+    client: &Client,
+    semaphore: &Arc<Semaphore>,
+    pending_handles: &mut Vec<(std::ops::Range<u64>, tokio::task::JoinHandle<Result<()>>)>,
+    dead_letter: &Option<Arc<Mutex<tokio::fs::File>>>,
+    tracker: &mut CheckpointTracker,
+) -> Result<()> {
+    let batch = std::mem::replace(current_batch, Vec::with_capacity(args.batch_size));
+    *current_batch_bytes = 0;
+
+    let _ = progress_tx.send(ProgressEvent::BatchSubmitted);
+    pending_handles.push((
+        start_line..end_line,
+        spawn_upload_task(
+            batch,
+            Arc::clone(semaphore),
             client.clone(),
             progress_tx.clone(),
             &args.endpoint,
@@ -123,33 +356,38 @@ async fn process_file(
             args.username.as_deref(),
             args.password.as_deref(),
             args.live,
-        ));
+            dead_letter.clone(),
+            args.incremental,
+        ),
+    ));
+
+    // When we hit our limit, start going through the queue
+    while pending_handles.len() >= args.max_pending_batches {
+        remove_completed(pending_handles, tracker).await?;
     }
 
-    // Leftover tasks
-    while !pending_handles.is_empty() {
-        remove_completed(&mut pending_handles).await?;
-    }
-
-    let _ = progress_tx.send(ProgressEvent::Finished);
-
     Ok(())
 }
 
-async fn remove_completed(handles: &mut Vec<tokio::task::JoinHandle<Result<()>>>) -> Result<usize> {
+async fn remove_completed(
+    handles: &mut Vec<(std::ops::Range<u64>, tokio::task::JoinHandle<Result<()>>)>,
+    tracker: &mut CheckpointTracker,
+) -> Result<usize> {
     if handles.is_empty() {
         return Ok(0);
     }
 
-    let (completed, idx, _) = futures::future::select_all(handles.iter_mut()).await;
+    let (completed, idx, _) =
+        futures::future::select_all(handles.iter_mut().map(|(_, handle)| handle)).await;
     completed
         .context("Task panicked")?
         .context("Upload task failed")?;
-    handles.remove(idx);
+    let (range, _) = handles.remove(idx);
+    tracker.complete_range(range.start, range.end)?;
     Ok(1)
 }
 
-fn replace_timestamps(line: &str) -> String {
+pub(crate) fn replace_timestamps(line: &str) -> String {
     static ISO_TIMESTAMP_RE: OnceLock<Regex> = OnceLock::new();
     let re = ISO_TIMESTAMP_RE.get_or_init(|| {
         // Match ISO 8601 timestamps like:
@@ -165,20 +403,28 @@ fn replace_timestamps(line: &str) -> String {
     re.replace_all(line, now.as_str()).to_string()
 }
 
+/// Stable `_id` derived from the SHA-256 of the line, used in non-live mode both to dedup via
+/// `create` (OpenSearch rejects a re-send of the same _id) and for `--incremental` lookups.
+fn document_id(line: &str) -> String {
+    hex::encode(&Sha256::digest(line.as_bytes())[..12])
+}
+
+/// Build the `_bulk` request body for `chunk`. In live mode, `chunk`'s lines are expected to
+/// already have `replace_timestamps` applied by the caller (`process_file`/`kafka_source::run`),
+/// so the regex runs once per line instead of once here and once more wherever the chunk's byte
+/// size was estimated.
 fn create_bulk_body(chunk: &[String], index: &str, live: bool) -> String {
     let mut bulk_body = String::new();
     for line in chunk {
         // In live mode, skip the _id field to let OpenSearch generate it
         if live {
             bulk_body.push_str(&format!("{{\"create\":{{\"_index\":\"{}\"}}}}\n", index));
-            // Replace timestamps with current time in live mode
-            let updated_line = replace_timestamps(line);
-            bulk_body.push_str(&updated_line);
+            bulk_body.push_str(line);
         } else {
-            let id = hex::encode(&Sha256::digest(line.as_bytes())[..12]);
             bulk_body.push_str(&format!(
                 "{{\"create\":{{\"_index\":\"{}\",\"_id\":\"{}\"}}}}\n",
-                index, id
+                index,
+                document_id(line)
             ));
             bulk_body.push_str(line);
         }
@@ -187,7 +433,171 @@ fn create_bulk_body(chunk: &[String], index: &str, live: bool) -> String {
     bulk_body
 }
 
-fn spawn_upload_task(
+#[derive(Deserialize)]
+struct MgetDoc {
+    #[serde(default)]
+    found: bool,
+}
+
+#[derive(Deserialize)]
+struct MgetResponseBody {
+    docs: Vec<MgetDoc>,
+}
+
+/// Issue `_mget` for the batch's computed IDs and drop lines whose document already exists,
+/// so a rerun against a partially-loaded index only resends what's missing.
+async fn filter_known_documents(
+    client: &Client,
+    endpoint: &str,
+    index: &str,
+    username: Option<&str>,
+    password: Option<&str>,
+    chunk: Vec<String>,
+) -> Result<(Vec<String>, usize)> {
+    let ids: Vec<String> = chunk.iter().map(|line| document_id(line)).collect();
+    let mget_url = format!("{}/{}/_mget", endpoint, index);
+
+    let mut request = client
+        .post(&mget_url)
+        .json(&serde_json::json!({ "ids": ids }));
+    if let (Some(user), Some(pass)) = (username, password) {
+        request = request.basic_auth(user, Some(pass));
+    }
+
+    let response = request
+        .send()
+        .await
+        .context("Failed to send _mget request")?
+        .error_for_status()
+        .context("_mget request failed")?;
+    let body: MgetResponseBody = response
+        .json()
+        .await
+        .context("Failed to parse _mget response")?;
+
+    partition_known_documents(chunk, &body.docs)
+}
+
+/// Split `chunk` into lines whose `_mget` lookup came back missing (still need uploading) and a
+/// count of lines whose document was already found. Pulled out of `filter_known_documents` so the
+/// dedup logic can be unit tested without a live `_mget` round trip.
+///
+/// `_mget` is documented to return `docs` in the same order and count as the requested `ids`, but
+/// a malformed or truncated response would otherwise `zip` down to the shorter length and silently
+/// drop the extra lines from both `remaining` and `skipped`, rather than uploading them; fail loud
+/// instead so a bad response doesn't look like a clean dedup pass.
+fn partition_known_documents(chunk: Vec<String>, docs: &[MgetDoc]) -> Result<(Vec<String>, usize)> {
+    anyhow::ensure!(
+        docs.len() == chunk.len(),
+        "_mget returned {} docs for {} requested ids",
+        docs.len(),
+        chunk.len()
+    );
+
+    let mut remaining = Vec::with_capacity(chunk.len());
+    let mut skipped = 0;
+    for (line, doc) in chunk.into_iter().zip(docs.iter()) {
+        if doc.found {
+            skipped += 1;
+        } else {
+            remaining.push(line);
+        }
+    }
+    Ok((remaining, skipped))
+}
+
+/// A single `{"create": {...}}` (or similar) entry in a `_bulk` response's `items` array.
+#[derive(Deserialize)]
+struct BulkItemResult {
+    status: u16,
+    #[serde(default)]
+    error: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct BulkResponseBody {
+    errors: bool,
+    items: Vec<HashMap<String, BulkItemResult>>,
+}
+
+/// Outcome of reconciling a batch's source lines against the `_bulk` response: which of the
+/// same-index line/item pairs succeeded outright, were skipped as already-indexed, need to be
+/// retried, or failed permanently (with a reason, for the dead-letter file).
+struct BulkOutcome {
+    succeeded: usize,
+    skipped: usize,
+    retry: Vec<String>,
+    failed: Vec<(String, String)>,
+}
+
+/// Walk `items` by index (indices align with the action/source line pairs we sent) and bucket
+/// each source line by what the cluster reported for it.
+///
+/// `items` is documented to come back in the same order and count as the sent action/source
+/// pairs, but a malformed or truncated response would otherwise `zip` down to the shorter length
+/// and silently drop the extra lines from every bucket -- never retried, never dead-lettered,
+/// never counted. Fail loud instead, the same way `partition_known_documents` does for `_mget`.
+fn reconcile_bulk_response(chunk: &[String], body: &BulkResponseBody) -> Result<BulkOutcome> {
+    anyhow::ensure!(
+        body.items.len() == chunk.len(),
+        "_bulk returned {} items for {} sent lines",
+        body.items.len(),
+        chunk.len()
+    );
+
+    let mut outcome = BulkOutcome {
+        succeeded: 0,
+        skipped: 0,
+        retry: Vec::new(),
+        failed: Vec::new(),
+    };
+
+    for (line, item) in chunk.iter().zip(body.items.iter()) {
+        let Some(result) = item.values().next() else {
+            continue;
+        };
+
+        match result.status {
+            200 | 201 => outcome.succeeded += 1,
+            409 => outcome.skipped += 1,
+            429 | 502 | 503 => outcome.retry.push(line.clone()),
+            _ => {
+                let reason = result
+                    .error
+                    .as_ref()
+                    .map(|e| e.to_string())
+                    .unwrap_or_else(|| format!("status {}", result.status));
+                outcome.failed.push((line.clone(), reason));
+            }
+        }
+    }
+
+    Ok(outcome)
+}
+
+async fn write_dead_letters(
+    dead_letter: &Option<Arc<Mutex<tokio::fs::File>>>,
+    failed: &[(String, String)],
+) -> Result<()> {
+    let Some(dead_letter) = dead_letter else {
+        return Ok(());
+    };
+    if failed.is_empty() {
+        return Ok(());
+    }
+
+    let mut file = dead_letter.lock().await;
+    for (line, reason) in failed {
+        file.write_all(line.as_bytes()).await?;
+        file.write_all(b"\t").await?;
+        file.write_all(reason.as_bytes()).await?;
+        file.write_all(b"\n").await?;
+    }
+    file.flush().await?;
+    Ok(())
+}
+
+pub(crate) fn spawn_upload_task(
     chunk: Vec<String>,
     semaphore: Arc<Semaphore>,
     client: Client,
@@ -197,27 +607,51 @@ fn spawn_upload_task(
     username: Option<&str>,
     password: Option<&str>,
     live: bool,
+    dead_letter: Option<Arc<Mutex<tokio::fs::File>>>,
+    incremental: bool,
 ) -> tokio::task::JoinHandle<Result<()>> {
     let bulk_url = format!("{}/_bulk", endpoint);
+    let endpoint = endpoint.to_string();
     let index = index.to_string();
     let username = username.map(|s| s.to_string());
     let password = password.map(|s| s.to_string());
 
     tokio::spawn(async move {
         let _permit = semaphore.acquire().await?;
-        let bulk_body = create_bulk_body(&chunk, &index, live);
-
         let _ = progress_tx.send(ProgressEvent::BatchStarted);
 
         let max_retries = 5;
         let mut retry_count = 0;
         let mut delay_ms = 500u64;
+        let mut chunk = chunk;
+
+        // Incremental mode only makes sense with the deterministic (non-live) _id scheme.
+        if incremental && !live {
+            let (remaining, skipped) = filter_known_documents(
+                &client,
+                &endpoint,
+                &index,
+                username.as_deref(),
+                password.as_deref(),
+                chunk,
+            )
+            .await?;
+            chunk = remaining;
+            let _ = progress_tx.send(ProgressEvent::DocumentsSkipped(skipped));
+
+            if chunk.is_empty() {
+                let _ = progress_tx.send(ProgressEvent::BatchCompleted);
+                return Ok(());
+            }
+        }
 
         loop {
+            let bulk_body = create_bulk_body(&chunk, &index, live);
+            let _ = progress_tx.send(ProgressEvent::BatchBytesSubmitted(bulk_body.len()));
             let mut request = client
                 .post(&bulk_url)
                 .header("Content-Type", "application/x-ndjson")
-                .body(bulk_body.clone());
+                .body(bulk_body);
 
             if let (Some(user), Some(pass)) = (&username, &password) {
                 request = request.basic_auth(user, Some(pass));
@@ -225,7 +659,8 @@ fn spawn_upload_task(
 
             let response = request.send().await.context("Failed to send request")?;
 
-            // Special case: exponential backoff for 429s
+            // Special case: exponential backoff for 429s on the whole request (cluster is
+            // rejecting the batch outright, not reporting per-item failures).
             if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS
                 && retry_count < max_retries
             {
@@ -235,11 +670,173 @@ fn spawn_upload_task(
                 continue;
             }
 
-            response.error_for_status().context("Request failed")?;
-            break;
+            let response = response.error_for_status().context("Request failed")?;
+            let body_text = response
+                .text()
+                .await
+                .context("Failed to read bulk response body")?;
+            let body: BulkResponseBody =
+                serde_json::from_str(&body_text).context("Failed to parse bulk response")?;
+
+            if !body.errors {
+                let _ = progress_tx.send(ProgressEvent::DocumentsSucceeded(chunk.len()));
+                break;
+            }
+
+            let outcome = reconcile_bulk_response(&chunk, &body)?;
+            let _ = progress_tx.send(ProgressEvent::DocumentsSucceeded(outcome.succeeded));
+            let _ = progress_tx.send(ProgressEvent::DocumentsSkipped(outcome.skipped));
+            let _ = progress_tx.send(ProgressEvent::DocumentsFailed(outcome.failed.len()));
+            write_dead_letters(&dead_letter, &outcome.failed).await?;
+
+            if outcome.retry.is_empty() {
+                break;
+            }
+            if retry_count >= max_retries {
+                // Out of retries: whatever's left is a permanent failure too.
+                let exhausted: Vec<(String, String)> = outcome
+                    .retry
+                    .into_iter()
+                    .map(|line| (line, "exhausted retries on retryable status".to_string()))
+                    .collect();
+                let _ = progress_tx.send(ProgressEvent::DocumentsFailed(exhausted.len()));
+                write_dead_letters(&dead_letter, &exhausted).await?;
+                break;
+            }
+
+            retry_count += 1;
+            tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
+            delay_ms *= 2;
+            chunk = outcome.retry;
         }
 
         let _ = progress_tx.send(ProgressEvent::BatchCompleted);
         Ok(())
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bulk_item(status: u16) -> HashMap<String, BulkItemResult> {
+        let mut item = HashMap::new();
+        item.insert(
+            "create".to_string(),
+            BulkItemResult {
+                status,
+                error: None,
+            },
+        );
+        item
+    }
+
+    #[test]
+    fn test_reconcile_bulk_response_buckets_by_status() {
+        let chunk = vec![
+            "ok".to_string(),
+            "dup".to_string(),
+            "throttled".to_string(),
+            "bad".to_string(),
+        ];
+        let body = BulkResponseBody {
+            errors: true,
+            items: vec![
+                bulk_item(201),
+                bulk_item(409),
+                bulk_item(429),
+                bulk_item(400),
+            ],
+        };
+
+        let outcome = reconcile_bulk_response(&chunk, &body).unwrap();
+
+        assert_eq!(outcome.succeeded, 1);
+        assert_eq!(outcome.skipped, 1);
+        assert_eq!(outcome.retry, vec!["throttled".to_string()]);
+        assert_eq!(outcome.failed.len(), 1);
+        assert_eq!(outcome.failed[0].0, "bad");
+        assert_eq!(outcome.failed[0].1, "status 400");
+    }
+
+    #[test]
+    fn test_reconcile_bulk_response_prefers_error_reason_over_status() {
+        let chunk = vec!["bad".to_string()];
+        let mut item = HashMap::new();
+        item.insert(
+            "create".to_string(),
+            BulkItemResult {
+                status: 400,
+                error: Some(serde_json::json!({"type": "mapper_parsing_exception"})),
+            },
+        );
+        let body = BulkResponseBody {
+            errors: true,
+            items: vec![item],
+        };
+
+        let outcome = reconcile_bulk_response(&chunk, &body).unwrap();
+
+        assert_eq!(outcome.failed.len(), 1);
+        assert_eq!(outcome.failed[0].1, "{\"type\":\"mapper_parsing_exception\"}");
+    }
+
+    #[test]
+    fn test_reconcile_bulk_response_errors_on_mismatched_length() {
+        let chunk = vec!["a".to_string(), "b".to_string()];
+        let body = BulkResponseBody {
+            errors: true,
+            items: vec![bulk_item(201)],
+        };
+
+        assert!(reconcile_bulk_response(&chunk, &body).is_err());
+    }
+
+    #[test]
+    fn test_bulk_entry_size_matches_create_bulk_body_in_live_mode() {
+        // In live mode, callers are expected to run `replace_timestamps` once and pass the same
+        // prepared line to both `bulk_entry_size` and `create_bulk_body`, so the raw line's
+        // timestamp format (millis or not) no longer matters to either.
+        let line = replace_timestamps("[2024-11-20T18:35:12Z] started up");
+        let chunk = vec![line.clone()];
+
+        let size = bulk_entry_size(&line, "my-index", true);
+        let body = create_bulk_body(&chunk, "my-index", true);
+
+        assert_eq!(size, body.len());
+    }
+
+    #[test]
+    fn test_bulk_entry_size_matches_create_bulk_body_in_non_live_mode() {
+        let line = "plain log line".to_string();
+        let chunk = vec![line.clone()];
+
+        let size = bulk_entry_size(&line, "my-index", false);
+        let body = create_bulk_body(&chunk, "my-index", false);
+
+        assert_eq!(size, body.len());
+    }
+
+    #[test]
+    fn test_partition_known_documents_splits_found_from_missing() {
+        let chunk = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let docs = vec![
+            MgetDoc { found: true },
+            MgetDoc { found: false },
+            MgetDoc { found: true },
+        ];
+
+        let (remaining, skipped) = partition_known_documents(chunk, &docs).unwrap();
+
+        assert_eq!(remaining, vec!["b".to_string()]);
+        assert_eq!(skipped, 2);
+    }
+
+    #[test]
+    fn test_partition_known_documents_errors_on_mismatched_length() {
+        let chunk = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let docs = vec![MgetDoc { found: true }, MgetDoc { found: false }];
+
+        assert!(partition_known_documents(chunk, &docs).is_err());
+    }
+}
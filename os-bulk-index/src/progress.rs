@@ -1,61 +1,313 @@
 use anyhow::Result;
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::time::Instant;
 use tokio::sync::mpsc;
 
+/// Smoothing factor for the throughput exponential moving average. Higher reacts faster to
+/// bursts, lower rides out noise; 0.3 tracks the TiKV/Turborepo-style ingest reporters we're
+/// mirroring here.
+const THROUGHPUT_EMA_ALPHA: f64 = 0.3;
+
+/// Minimum interval between throughput samples, so back-to-back events (e.g. a tight retry
+/// loop) don't produce a divide-by-near-zero instantaneous rate.
+const MIN_SAMPLE_INTERVAL_SECS: f64 = 0.05;
+
 #[derive(Debug)]
 pub enum ProgressEvent {
     LineRead,
+    /// Raw bytes consumed from the input source, counted before decompression so it tracks
+    /// against the on-disk (possibly compressed) file size rather than the decoded line output.
+    BytesRead(u64),
     BatchSubmitted,
     BatchStarted,
     BatchCompleted,
+    /// Size in bytes of a batch's bulk request body, emitted once the body is built.
+    BatchBytesSubmitted(usize),
+    /// Documents confirmed indexed (HTTP 200/201 from the `_bulk` response).
+    DocumentsSucceeded(usize),
+    /// Documents that already existed (409 conflict on `create`), treated as already-indexed.
+    DocumentsSkipped(usize),
+    /// Documents that failed permanently (not retried, or retries exhausted).
+    DocumentsFailed(usize),
     Finished,
 }
 
+/// Tracks docs/sec and bytes/sec as an exponential moving average over wall-clock time, so the
+/// displayed rate reflects recent throughput rather than the lifetime average.
+struct Throughput {
+    last_sample: Instant,
+    last_docs: u64,
+    last_bytes: u64,
+    last_bytes_read: u64,
+    total_docs: u64,
+    total_bytes: u64,
+    total_bytes_read: u64,
+    docs_per_sec: f64,
+    bytes_per_sec: f64,
+    bytes_read_per_sec: f64,
+}
+
+impl Throughput {
+    fn new() -> Self {
+        Self {
+            last_sample: Instant::now(),
+            last_docs: 0,
+            last_bytes: 0,
+            last_bytes_read: 0,
+            total_docs: 0,
+            total_bytes: 0,
+            total_bytes_read: 0,
+            docs_per_sec: 0.0,
+            bytes_per_sec: 0.0,
+            bytes_read_per_sec: 0.0,
+        }
+    }
+
+    fn record_docs(&mut self, n: u64) {
+        self.total_docs += n;
+        self.sample();
+    }
+
+    fn record_bytes(&mut self, n: u64) {
+        self.total_bytes += n;
+        self.sample();
+    }
+
+    /// Track raw input bytes consumed (from `ProgressEvent::BytesRead`), separately from
+    /// `record_bytes`'s submitted-bulk-body bytes, so the no-`--limit` ETA fallback can be
+    /// computed against how fast the input file is actually being read.
+    fn record_bytes_read(&mut self, n: u64) {
+        self.total_bytes_read += n;
+        self.sample();
+    }
+
+    fn sample(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_sample).as_secs_f64();
+        if elapsed < MIN_SAMPLE_INTERVAL_SECS {
+            return;
+        }
+
+        let instant_docs_rate = (self.total_docs - self.last_docs) as f64 / elapsed;
+        let instant_bytes_rate = (self.total_bytes - self.last_bytes) as f64 / elapsed;
+        let instant_bytes_read_rate = (self.total_bytes_read - self.last_bytes_read) as f64 / elapsed;
+        self.docs_per_sec =
+            THROUGHPUT_EMA_ALPHA * instant_docs_rate + (1.0 - THROUGHPUT_EMA_ALPHA) * self.docs_per_sec;
+        self.bytes_per_sec = THROUGHPUT_EMA_ALPHA * instant_bytes_rate
+            + (1.0 - THROUGHPUT_EMA_ALPHA) * self.bytes_per_sec;
+        self.bytes_read_per_sec = THROUGHPUT_EMA_ALPHA * instant_bytes_read_rate
+            + (1.0 - THROUGHPUT_EMA_ALPHA) * self.bytes_read_per_sec;
+
+        self.last_sample = now;
+        self.last_docs = self.total_docs;
+        self.last_bytes = self.total_bytes;
+        self.last_bytes_read = self.total_bytes_read;
+    }
+
+    /// Render the live status line: throughput, total bytes sent, and an ETA computed from
+    /// remaining lines (when `lines_limit` is known) or remaining input bytes (when `total_bytes`
+    /// is known instead, the common case for a local file run without `--limit`).
+    fn status_line(
+        &self,
+        lines_read: u64,
+        lines_limit: Option<usize>,
+        bytes_read: u64,
+        total_bytes: Option<u64>,
+    ) -> String {
+        let mb_per_sec = self.bytes_per_sec / 1_000_000.0;
+        let total_mb = self.total_bytes as f64 / 1_000_000.0;
+
+        let eta = match lines_limit {
+            Some(limit) if self.docs_per_sec > 0.0 => {
+                let remaining = (limit as u64).saturating_sub(lines_read) as f64;
+                let eta_secs = remaining / self.docs_per_sec;
+                format!("{:.0}s", eta_secs)
+            }
+            Some(_) => "calculating...".to_string(),
+            None => match total_bytes {
+                Some(total) if self.bytes_read_per_sec > 0.0 => {
+                    let remaining = total.saturating_sub(bytes_read) as f64;
+                    let eta_secs = remaining / self.bytes_read_per_sec;
+                    format!("{:.0}s", eta_secs)
+                }
+                Some(_) => "calculating...".to_string(),
+                None => "unknown (no --limit)".to_string(),
+            },
+        };
+
+        format!(
+            "{:.0} docs/s, {:.2} MB/s, {:.1} MB sent, ETA {}",
+            self.docs_per_sec, mb_per_sec, total_mb, eta
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    /// `Throughput::sample` gates on `MIN_SAMPLE_INTERVAL_SECS`, so tests that want a sample to
+    /// actually land need to sleep past it first.
+    fn sleep_past_min_sample_interval() {
+        sleep(Duration::from_secs_f64(MIN_SAMPLE_INTERVAL_SECS * 2.0));
+    }
+
+    #[test]
+    fn test_sample_within_min_interval_is_dropped() {
+        let mut throughput = Throughput::new();
+        throughput.record_docs(100);
+        // No sleep: the second sample should be dropped and the rate should stay at zero.
+        throughput.record_docs(100);
+        assert_eq!(throughput.docs_per_sec, 0.0);
+    }
+
+    #[test]
+    fn test_ema_smooths_toward_instantaneous_rate_without_jumping_straight_to_it() {
+        let start = Instant::now();
+        let mut throughput = Throughput::new();
+        sleep_past_min_sample_interval();
+        throughput.record_docs(1000);
+        let window = start.elapsed().as_secs_f64();
+
+        assert!(throughput.docs_per_sec > 0.0);
+        // The EMA starts from a zero rate, so the first sample lands at `THROUGHPUT_EMA_ALPHA`
+        // times the instantaneous rate rather than jumping straight to it. `window` spans at
+        // least the sampled interval, so `1000 / window` is a lower bound on that raw rate.
+        let instant_rate = 1000.0 / window;
+        assert!(throughput.docs_per_sec < instant_rate);
+    }
+
+    #[test]
+    fn test_status_line_eta_uses_lines_remaining_when_limit_is_set() {
+        let mut throughput = Throughput::new();
+        sleep_past_min_sample_interval();
+        throughput.record_docs(10);
+
+        let line = throughput.status_line(10, Some(20), 0, None);
+        assert!(line.contains("ETA"));
+        assert!(!line.contains("unknown"));
+    }
+
+    #[test]
+    fn test_status_line_eta_falls_back_to_bytes_remaining_without_limit() {
+        let mut throughput = Throughput::new();
+        sleep_past_min_sample_interval();
+        throughput.record_bytes_read(1000);
+
+        let line = throughput.status_line(10, None, 1000, Some(2000));
+        assert!(line.contains("ETA"));
+        assert!(!line.contains("unknown"));
+    }
+
+    #[test]
+    fn test_status_line_eta_is_unknown_without_limit_or_total_bytes() {
+        let throughput = Throughput::new();
+        let line = throughput.status_line(10, None, 1000, None);
+        assert!(line.contains("ETA unknown (no --limit)"));
+    }
+
+    #[test]
+    fn test_status_line_eta_is_calculating_before_first_rate_sample() {
+        let throughput = Throughput::new();
+        // No samples recorded yet, so `docs_per_sec` is still zero even though a limit is set.
+        let line = throughput.status_line(0, Some(20), 0, None);
+        assert!(line.contains("ETA calculating..."));
+    }
+}
+
 pub struct ProgressBars {
     lines: ProgressBar,
+    bytes: ProgressBar,
     submitted: ProgressBar,
     in_flight: ProgressBar,
     completed: ProgressBar,
+    succeeded: ProgressBar,
+    skipped: ProgressBar,
+    failed: ProgressBar,
+    throughput: ProgressBar,
 }
 
-pub fn setup_progress_bars(lines_to_read: Option<usize>) -> ProgressBars {
+/// `total_bytes` is the on-disk size of the input when known (absent for stdin or a remote URL),
+/// used to size a real bar with byte-accurate position, rate, and ETA rather than a bare spinner.
+pub fn setup_progress_bars(lines_to_read: Option<usize>, total_bytes: Option<u64>) -> ProgressBars {
     let multi = MultiProgress::new();
     let style = ProgressStyle::default_spinner()
         .template("{spinner:.green} [{elapsed_precise}] {prefix}: {pos} {msg}")
         .unwrap();
 
     let lines = multi.add(ProgressBar::new(lines_to_read.unwrap_or(0) as u64));
+    let bytes = multi.add(match total_bytes {
+        Some(total) => ProgressBar::new(total),
+        None => ProgressBar::new_spinner(),
+    });
     let submitted = multi.add(ProgressBar::new_spinner());
     let in_flight = multi.add(ProgressBar::new_spinner());
     let completed = multi.add(ProgressBar::new_spinner());
+    let succeeded = multi.add(ProgressBar::new_spinner());
+    let skipped = multi.add(ProgressBar::new_spinner());
+    let failed = multi.add(ProgressBar::new_spinner());
+    let throughput = multi.add(ProgressBar::new_spinner());
 
     lines.set_style(style.clone());
+    bytes.set_style(match total_bytes {
+        Some(_) => ProgressStyle::default_bar()
+            .template(
+                "{spinner:.green} [{elapsed_precise}] {prefix}: [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, eta {eta})",
+            )
+            .unwrap()
+            .progress_chars("#>-"),
+        None => ProgressStyle::default_spinner()
+            .template("{spinner:.green} [{elapsed_precise}] {prefix}: {bytes} ({bytes_per_sec})")
+            .unwrap(),
+    });
     submitted.set_style(style.clone());
     in_flight.set_style(style.clone());
-    completed.set_style(style);
+    completed.set_style(style.clone());
+    succeeded.set_style(style.clone());
+    skipped.set_style(style.clone());
+    failed.set_style(style.clone());
+    throughput.set_style(style);
 
     lines.set_prefix("Lines read");
+    bytes.set_prefix("Bytes read");
     submitted.set_prefix("Batches pending");
     in_flight.set_prefix("Requests in flight");
     completed.set_prefix("Batches completed");
+    succeeded.set_prefix("Documents indexed");
+    skipped.set_prefix("Documents skipped");
+    failed.set_prefix("Documents failed");
+    throughput.set_prefix("Throughput");
 
     ProgressBars {
         lines,
+        bytes,
         submitted,
         in_flight,
         completed,
+        succeeded,
+        skipped,
+        failed,
+        throughput,
     }
 }
 
 pub async fn handle_progress_events(
     mut rx: mpsc::UnboundedReceiver<ProgressEvent>,
     lines_limit: Option<usize>,
+    total_bytes: Option<u64>,
 ) -> Result<()> {
-    let progress = setup_progress_bars(lines_limit);
+    let progress = setup_progress_bars(lines_limit, total_bytes);
+    let mut throughput = Throughput::new();
 
     while let Some(event) = rx.recv().await {
         match event {
             ProgressEvent::LineRead => progress.lines.inc(1),
+            ProgressEvent::BytesRead(n) => {
+                progress.bytes.inc(n);
+                throughput.record_bytes_read(n);
+            }
             ProgressEvent::BatchSubmitted => progress.submitted.inc(1),
             ProgressEvent::BatchStarted => {
                 progress.submitted.dec(1);
@@ -65,14 +317,38 @@ pub async fn handle_progress_events(
                 progress.in_flight.dec(1);
                 progress.completed.inc(1);
             }
+            ProgressEvent::BatchBytesSubmitted(n) => throughput.record_bytes(n as u64),
+            ProgressEvent::DocumentsSucceeded(n) => {
+                progress.succeeded.inc(n as u64);
+                throughput.record_docs(n as u64);
+            }
+            ProgressEvent::DocumentsSkipped(n) => progress.skipped.inc(n as u64),
+            ProgressEvent::DocumentsFailed(n) => progress.failed.inc(n as u64),
             ProgressEvent::Finished => break,
         }
+
+        progress.throughput.set_message(throughput.status_line(
+            progress.lines.position(),
+            lines_limit,
+            progress.bytes.position(),
+            total_bytes,
+        ));
     }
 
     progress.lines.finish_with_message("Done");
+    progress.bytes.finish_with_message("Done");
     progress.submitted.finish_with_message("Done");
     progress.in_flight.finish_with_message("Done");
     progress.completed.finish_with_message("Done");
+    progress.succeeded.finish_with_message("Done");
+    progress.skipped.finish_with_message("Done");
+    progress.failed.finish_with_message("Done");
+    progress.throughput.finish_with_message(throughput.status_line(
+        progress.lines.position(),
+        lines_limit,
+        progress.bytes.position(),
+        total_bytes,
+    ));
 
     Ok(())
 }
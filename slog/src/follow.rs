@@ -0,0 +1,211 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufRead};
+use std::path::PathBuf;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, SendError, SyncSender, sync_channel};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use glob::glob;
+
+use crate::{CHAN_CAPACITY, Filters, LogEntry, LogParser, LogReceiver, epoch_sentinel, send_buf};
+
+/// How often a tailed file is polled for new data after hitting EOF.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// How long a buffered-but-unflushed entry sits idle before it's flushed anyway, so a trailing
+/// partial entry (the last lines written before the process was started, or a pause in writes)
+/// doesn't wait forever for a line that would confirm it's complete.
+const IDLE_FLUSH_TIMEOUT: Duration = Duration::from_millis(500);
+/// How often newly matching files (created after startup) are picked up.
+const NEW_FILE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// How long `reorder` holds an entry before emitting it, to give a quiet file's slower-arriving
+/// entry a chance to land and sort ahead of one from a busier file. Same order of magnitude as
+/// `IDLE_FLUSH_TIMEOUT`: long enough to absorb normal scheduling/poll jitter across files, short
+/// enough that a live tail still feels live.
+const REORDER_WINDOW: Duration = Duration::from_millis(400);
+/// How often `reorder` re-checks its buffer for entries that have cleared `REORDER_WINDOW`.
+const REORDER_TICK: Duration = Duration::from_millis(50);
+
+/// Fold a completed physical line into the multi-line entry buffer `buf`, flushing `buf` first
+/// if this line starts a new entry.
+fn append_line(
+    line: &str,
+    buf: &mut String,
+    tx: &SyncSender<LogEntry>,
+    parser: &LogParser,
+    filters: &Filters,
+    last_key: &mut DateTime<Utc>,
+) -> Result<(), SendError<()>> {
+    if parser.is_entry_start(line) {
+        send_buf(buf, tx, parser, filters, last_key)?;
+    }
+    if !buf.is_empty() {
+        buf.push('\n');
+    }
+    buf.push_str(line);
+    Ok(())
+}
+
+/// Like `send_lines`, but never stops at EOF: it polls for newly appended data, honoring the
+/// same multi-line grouping (`LogParser::is_entry_start`), and flushes a trailing partial entry after
+/// `IDLE_FLUSH_TIMEOUT` of no new lines instead of waiting for the next entry's header to arrive.
+fn tail_lines(
+    handle: File,
+    tx: SyncSender<LogEntry>,
+    parser: &LogParser,
+    filters: &Filters,
+) -> Result<(), SendError<()>> {
+    let mut reader = io::BufReader::new(handle);
+    let mut buf = String::new();
+    // A `read_line` that catches the writer mid-flush can return a line with no trailing `\n`;
+    // the rest of that same physical line arrives on a later poll. Accumulate here instead of
+    // treating each `read_line` result as a finished line, so a header split across two polls
+    // doesn't get a synthetic `\n` inserted in the middle of it.
+    let mut pending_line = String::new();
+    let mut line = String::new();
+    let mut last_activity = Instant::now();
+    let mut last_key = epoch_sentinel();
+
+    loop {
+        line.clear();
+        match reader.read_line(&mut line) {
+            Ok(0) => {
+                if !pending_line.is_empty() && last_activity.elapsed() >= IDLE_FLUSH_TIMEOUT {
+                    // The writer stopped without ever appending a trailing newline (e.g. the
+                    // file's last line at EOF); treat what we have as the finished line rather
+                    // than waiting forever for a `\n` that isn't coming.
+                    let line = std::mem::take(&mut pending_line);
+                    append_line(&line, &mut buf, &tx, parser, filters, &mut last_key)?;
+                }
+                if !buf.is_empty() && last_activity.elapsed() >= IDLE_FLUSH_TIMEOUT {
+                    send_buf(&mut buf, &tx, parser, filters, &mut last_key)?;
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+            Ok(_) => {
+                let complete = line.ends_with('\n');
+                pending_line.push_str(line.trim_end_matches('\n'));
+                last_activity = Instant::now();
+                if complete {
+                    let line = std::mem::take(&mut pending_line);
+                    append_line(&line, &mut buf, &tx, parser, filters, &mut last_key)?;
+                }
+            }
+            // A transient read error (e.g. file rotated out from under us): back off and retry
+            // rather than tearing down the tail.
+            Err(_) => thread::sleep(POLL_INTERVAL),
+        }
+    }
+}
+
+/// Hold entries arriving on `rx` for up to `REORDER_WINDOW` so they can be re-emitted on `tx` in
+/// `sort_key` order rather than raw arrival order, then drain whatever's left (in order) once
+/// `rx` disconnects. A strict merge (as `merge_receivers` does for the non-follow path) would
+/// stall on a quiet file while a busier one has entries ready; bounding the wait instead caps
+/// how stale an interleaving can be without blocking on files that may never write again.
+fn reorder(rx: Receiver<LogEntry>, tx: SyncSender<LogEntry>) {
+    let mut buf: Vec<(Instant, LogEntry)> = Vec::new();
+
+    loop {
+        match rx.recv_timeout(REORDER_TICK) {
+            Ok(entry) => buf.push((Instant::now(), entry)),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                flush_matured(&mut buf, &tx, Duration::ZERO);
+                return;
+            }
+        }
+        if flush_matured(&mut buf, &tx, REORDER_WINDOW).is_err() {
+            return;
+        }
+    }
+}
+
+/// Split `buf` into entries held at least `min_age` and everything else, send the former in
+/// `sort_key` order, and leave the rest buffered for a later call.
+fn flush_matured(
+    buf: &mut Vec<(Instant, LogEntry)>,
+    tx: &SyncSender<LogEntry>,
+    min_age: Duration,
+) -> Result<(), SendError<LogEntry>> {
+    let now = Instant::now();
+    let (mut ready, pending): (Vec<_>, Vec<_>) = std::mem::take(buf)
+        .into_iter()
+        .partition(|(arrived, _)| now.duration_since(*arrived) >= min_age);
+    *buf = pending;
+
+    ready.sort_by_key(|(_, entry)| entry.sort_key);
+    for (_, entry) in ready {
+        tx.send(entry)?;
+    }
+    Ok(())
+}
+
+fn spawn_tail<'s>(
+    path: PathBuf,
+    tx: SyncSender<LogEntry>,
+    scope: &'s thread::Scope<'s, '_>,
+    parser: &'s LogParser,
+    filters: &'s Filters,
+) {
+    scope.spawn(move || match File::open(&path) {
+        Ok(handle) => {
+            let _ = tail_lines(handle, tx, parser, filters);
+        }
+        Err(err) => {
+            eprintln!("Unable to open {}: {err}", path.to_string_lossy());
+        }
+    });
+}
+
+/// Continuously tail every file matching `patterns`, merged into one `LogReceiver`, picking up
+/// both appended lines (`tail -f`-style) and newly created files matching the glob patterns.
+///
+/// Unlike `merge_receivers`, the set of tailed files changes over time, so there's no fixed merge
+/// tree to build ahead of time: each tail forwards onto one shared channel as entries complete,
+/// and a `reorder` stage re-sorts that stream by `sort_key` within a `REORDER_WINDOW` bound before
+/// it reaches the caller. This means a multi-node `--follow` still comes out in chronological
+/// order as long as clock skew plus write latency across nodes stays under `REORDER_WINDOW`; an
+/// entry arriving later than that is emitted out of order rather than held indefinitely.
+pub fn run<'s>(
+    patterns: &[String],
+    scope: &'s thread::Scope<'s, '_>,
+    parser: &'s LogParser,
+    filters: &'s Filters,
+) -> LogReceiver {
+    let (tx, rx) = sync_channel(CHAN_CAPACITY);
+    let mut seen = HashSet::new();
+
+    for pattern in patterns {
+        let Ok(paths) = glob(pattern) else {
+            continue;
+        };
+        for path in paths.flatten() {
+            if seen.insert(path.clone()) {
+                spawn_tail(path, tx.clone(), scope, parser, filters);
+            }
+        }
+    }
+
+    let patterns = patterns.to_vec();
+    scope.spawn(move || {
+        loop {
+            thread::sleep(NEW_FILE_POLL_INTERVAL);
+            for pattern in &patterns {
+                let Ok(paths) = glob(pattern) else {
+                    continue;
+                };
+                for path in paths.flatten() {
+                    if seen.insert(path.clone()) {
+                        spawn_tail(path, tx.clone(), scope, parser, filters);
+                    }
+                }
+            }
+        }
+    });
+
+    let (out_tx, out_rx) = sync_channel(CHAN_CAPACITY);
+    scope.spawn(move || reorder(rx, out_tx));
+    out_rx.into_iter()
+}
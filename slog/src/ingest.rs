@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::LogReceiver;
+
+/// Max documents accumulated into one `_bulk` request body before it's flushed, whichever of
+/// this or `BULK_BATCH_BYTES` comes first.
+const BULK_BATCH_DOCS: usize = 500;
+/// Max accumulated body size (bytes) per `_bulk` request.
+const BULK_BATCH_BYTES: usize = 4_000_000;
+
+pub struct IngestConfig {
+    pub endpoint: String,
+    pub index: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub api_key: Option<String>,
+}
+
+/// A single `{"index": {...}}` (or similar) entry in a `_bulk` response's `items` array.
+#[derive(Deserialize)]
+struct BulkItemResult {
+    status: u16,
+    #[serde(default)]
+    error: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct BulkResponseBody {
+    errors: bool,
+    items: Vec<HashMap<String, BulkItemResult>>,
+}
+
+/// Batch the merged entries from `receiver` into `_bulk` NDJSON requests and POST them to
+/// `config.endpoint`, logging any per-item failures to stderr. Pulls lazily from `receiver`, so
+/// backpressure flows up through the existing sync_channel capacity rather than buffering the
+/// whole merged stream in memory.
+pub fn run_ingest(receiver: LogReceiver, config: &IngestConfig) {
+    let client = reqwest::blocking::Client::new();
+    let bulk_url = format!("{}/_bulk", config.endpoint.trim_end_matches('/'));
+    let action_line = format!("{{\"index\":{{\"_index\":\"{}\"}}}}\n", config.index);
+
+    let mut batch = Vec::with_capacity(BULK_BATCH_DOCS);
+    let mut batch_bytes = 0usize;
+
+    for entry in receiver {
+        let Ok(entry_json) = serde_json::to_string(&entry) else {
+            continue;
+        };
+        batch_bytes += action_line.len() + entry_json.len() + 1;
+        batch.push(entry_json);
+
+        if batch.len() >= BULK_BATCH_DOCS || batch_bytes >= BULK_BATCH_BYTES {
+            send_batch(&client, &bulk_url, &action_line, &batch, config);
+            batch.clear();
+            batch_bytes = 0;
+        }
+    }
+
+    if !batch.is_empty() {
+        send_batch(&client, &bulk_url, &action_line, &batch, config);
+    }
+}
+
+fn send_batch(
+    client: &reqwest::blocking::Client,
+    bulk_url: &str,
+    action_line: &str,
+    batch: &[String],
+    config: &IngestConfig,
+) {
+    let mut body = String::new();
+    for doc in batch {
+        body.push_str(action_line);
+        body.push_str(doc);
+        body.push('\n');
+    }
+
+    let mut request = client
+        .post(bulk_url)
+        .header("Content-Type", "application/x-ndjson")
+        .body(body);
+    if let (Some(user), Some(pass)) = (&config.username, &config.password) {
+        request = request.basic_auth(user, Some(pass));
+    } else if let Some(api_key) = &config.api_key {
+        request = request.header("Authorization", format!("ApiKey {api_key}"));
+    }
+
+    let response = match request.send() {
+        Ok(response) => response,
+        Err(err) => {
+            eprintln!("Bulk ingest request failed: {err}");
+            return;
+        }
+    };
+
+    let body_text = match response.text() {
+        Ok(text) => text,
+        Err(err) => {
+            eprintln!("Failed to read bulk response: {err}");
+            return;
+        }
+    };
+
+    let parsed: BulkResponseBody = match serde_json::from_str(&body_text) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("Failed to parse bulk response: {err}");
+            return;
+        }
+    };
+
+    if !parsed.errors {
+        return;
+    }
+
+    for (index, item) in parsed.items.iter().enumerate() {
+        let Some(result) = item.values().next() else {
+            continue;
+        };
+        if result.status >= 300 {
+            let reason = result
+                .error
+                .as_ref()
+                .map(|e| e.to_string())
+                .unwrap_or_else(|| format!("status {}", result.status));
+            eprintln!("Bulk ingest item {index} failed: {reason}");
+        }
+    }
+}
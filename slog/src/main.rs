@@ -1,26 +1,34 @@
+mod follow;
+mod ingest;
+mod serve;
+
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::{self, BufRead, BufWriter, Write};
 use std::path::PathBuf;
 use std::sync::mpsc::{SendError, SyncSender, sync_channel};
+use std::sync::OnceLock;
 use std::thread;
 
-use clap::Parser;
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+use clap::{Parser, Subcommand};
 use colored::{ColoredString, Colorize};
-use glob::{Paths, glob};
+use glob::{Paths, Pattern, glob};
 use itertools::merge;
 use regex::Regex;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+
+use ingest::IngestConfig;
 
 /// Max log lines to buffer in-memory per channel. 2k capacity ~ 1M memory per file.
 const CHAN_CAPACITY: usize = 2048;
 
-type LogReceiver = std::sync::mpsc::IntoIter<String>;
+type LogReceiver = std::sync::mpsc::IntoIter<LogEntry>;
 
 #[derive(Parser)]
 #[command(about = "OpenSearch log parser and viewer")]
 struct Args {
-    /// File patterns to process
-    #[arg(required = true)]
+    /// File patterns to process. Not used with `serve`, which takes its own patterns.
     patterns: Vec<String>,
 
     /// Disable colored output
@@ -30,12 +38,211 @@ struct Args {
     /// Output logs as NDJSON
     #[arg(long)]
     json: bool,
+
+    /// Keep watching matched files for new lines (and new files matching the patterns) instead
+    /// of exiting at EOF, like `tail -f` merged across nodes
+    #[arg(short = 'f', long)]
+    follow: bool,
+
+    /// Bulk-ingest the merged stream into this OpenSearch/Elasticsearch endpoint instead of
+    /// printing to stdout (e.g. https://host:9200)
+    #[arg(long)]
+    ingest: Option<String>,
+
+    /// Target index for --ingest
+    #[arg(long, default_value = "slog")]
+    index: String,
+
+    /// Username for HTTP basic auth when using --ingest
+    #[arg(long)]
+    username: Option<String>,
+
+    /// Password for HTTP basic auth when using --ingest
+    #[arg(long)]
+    password: Option<String>,
+
+    /// API key for HTTP ApiKey auth when using --ingest (used instead of username/password)
+    #[arg(long)]
+    api_key: Option<String>,
+
+    /// Only show entries at or above this severity (rank: DEBUG<TRACE<INFO<WARN<ERROR<FATAL)
+    #[arg(long)]
+    min_severity: Option<String>,
+
+    /// Only show entries of this log type
+    #[arg(long = "type", value_name = "TYPE")]
+    log_type: Option<String>,
+
+    /// Only show entries whose class matches this glob
+    #[arg(long)]
+    class: Option<String>,
+
+    /// Only show entries from this node id
+    #[arg(long)]
+    node: Option<String>,
+
+    /// Only show entries at or after this timestamp (RFC3339, matching the `@timestamp` field
+    /// entries are emitted with in `--json`/`serve` output)
+    #[arg(long)]
+    since: Option<String>,
+
+    /// Only show entries at or before this timestamp (RFC3339, matching `@timestamp`)
+    #[arg(long)]
+    until: Option<String>,
+
+    /// Only show HTTP entries with a status code at or above this
+    #[arg(long)]
+    status_gte: Option<u16>,
+
+    /// Only show HTTP entries with a status code at or below this
+    #[arg(long)]
+    status_lte: Option<u16>,
+
+    /// Only show HTTP entries with latency at or above this many milliseconds
+    #[arg(long)]
+    min_latency_ms: Option<u64>,
+
+    /// Load additional named log-format patterns from this TOML/JSON file, tried in priority
+    /// order ahead of the built-in formats
+    #[arg(long = "patterns", value_name = "FILE")]
+    custom_patterns: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-#[derive(Serialize)]
+/// Rank used by `--min-severity`, matching the order this project's severities are expected to
+/// escalate in (note TRACE sits above DEBUG here). Severities outside this list never get
+/// filtered out by `--min-severity`, since we can't judge their urgency.
+fn severity_rank(severity: &str) -> u8 {
+    match severity.trim() {
+        "DEBUG" => 0,
+        "TRACE" => 1,
+        "INFO" => 2,
+        "WARN" => 3,
+        "ERROR" => 4,
+        "FATAL" => 5,
+        _ => u8::MAX,
+    }
+}
+
+/// Filter predicates evaluated against each parsed `LogEntry` before it's sent across a channel,
+/// so filtering behaves identically regardless of color/JSON output mode.
+#[derive(Default)]
+struct Filters {
+    min_severity: Option<u8>,
+    log_type: Option<String>,
+    class: Option<Pattern>,
+    node: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+    status_gte: Option<u16>,
+    status_lte: Option<u16>,
+    min_latency_ms: Option<u64>,
+}
+
+impl Filters {
+    fn from_args(args: &Args) -> Self {
+        Self {
+            min_severity: args.min_severity.as_deref().map(severity_rank),
+            log_type: args.log_type.clone(),
+            class: args.class.as_deref().and_then(|pattern| Pattern::new(pattern).ok()),
+            node: args.node.clone(),
+            since: args.since.clone(),
+            until: args.until.clone(),
+            status_gte: args.status_gte,
+            status_lte: args.status_lte,
+            min_latency_ms: args.min_latency_ms,
+        }
+    }
+
+    fn matches(&self, entry: &LogEntry) -> bool {
+        if let Some(min_severity) = self.min_severity {
+            if severity_rank(&entry.severity) < min_severity {
+                return false;
+            }
+        }
+        if let Some(log_type) = &self.log_type {
+            if &entry.log_type != log_type {
+                return false;
+            }
+        }
+        if let Some(class) = &self.class {
+            if !class.matches(&entry.class) {
+                return false;
+            }
+        }
+        if let Some(node) = &self.node {
+            if &entry.node_id != node {
+                return false;
+            }
+        }
+        if let Some(since) = &self.since {
+            if &entry.timestamp_rfc3339 < since {
+                return false;
+            }
+        }
+        if let Some(until) = &self.until {
+            if &entry.timestamp_rfc3339 > until {
+                return false;
+            }
+        }
+        if let Some(status_gte) = self.status_gte {
+            match entry.response_status_code {
+                Some(code) if code >= status_gte => {}
+                _ => return false,
+            }
+        }
+        if let Some(status_lte) = self.status_lte {
+            match entry.response_status_code {
+                Some(code) if code <= status_lte => {}
+                _ => return false,
+            }
+        }
+        if let Some(min_latency_ms) = self.min_latency_ms {
+            match entry.response_latency_ms {
+                Some(latency) if latency >= min_latency_ms => {}
+                _ => return false,
+            }
+        }
+        true
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Serve the merged log stream over HTTP as chunked NDJSON at GET /logs
+    Serve {
+        /// File patterns to process
+        #[arg(required = true)]
+        patterns: Vec<String>,
+
+        /// Address to bind the HTTP server to
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        bind: String,
+    },
+}
+
+/// Field order matters: the derived `PartialOrd` compares fields in declaration order, and
+/// `sort_key` leads so `merge_receivers` orders entries by actual parsed time rather than the
+/// lexical order of `timestamp`'s raw text (which breaks as soon as a zone offset or an
+/// unparseable line is involved).
+#[derive(Serialize, PartialEq, PartialOrd)]
 struct LogEntry {
-    #[serde(rename = "@timestamp")]
+    /// Parsed `timestamp`, resolved by `resolve_sort_key` right before the entry is sent across
+    /// a channel. Not serialized directly; `timestamp_rfc3339` is what gets emitted.
+    #[serde(skip)]
+    sort_key: DateTime<Utc>,
+    /// Raw timestamp text as it appeared in the source line, kept around for terminal display.
+    /// `--since`/`--until` compare against `timestamp_rfc3339` instead, since that's the only
+    /// timestamp form a `--json`/`serve` consumer ever sees, and it's lexically sortable across
+    /// any source format including custom `--patterns`.
+    #[serde(skip)]
     timestamp: String,
+    /// RFC3339 rendering of `sort_key`, emitted as `@timestamp` so the NDJSON output is directly
+    /// ingestible by OpenSearch/Elasticsearch date mappings regardless of the source format.
+    #[serde(rename = "@timestamp")]
+    timestamp_rfc3339: String,
     log_type: String,
     severity: String,
     class: String,
@@ -61,16 +268,79 @@ struct LogEntry {
     exception_message: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     exception_trace: Option<String>,
+    /// Named capture groups from a `--patterns` entry that don't map onto a known `LogEntry`
+    /// field, serialized flat alongside the rest of the entry.
+    #[serde(flatten)]
+    extra: BTreeMap<String, String>,
+}
+
+/// A user-supplied pattern loaded via `--patterns`, compiled once and tried ahead of the
+/// built-in formats in the order it was declared.
+struct CustomPattern {
+    regex: Regex,
+    log_type: String,
+}
+
+/// On-disk shape of a `--patterns` file (TOML or JSON, chosen by extension).
+#[derive(Deserialize)]
+struct PatternsFile {
+    patterns: Vec<PatternDef>,
+}
+
+#[derive(Deserialize)]
+struct PatternDef {
+    name: String,
+    regex: String,
+    log_type: String,
+}
+
+/// Load and compile the patterns declared in `path`. Errors are logged to stderr rather than
+/// aborting the program over a malformed config: a file that fails to parse (TOML or JSON) is
+/// dropped whole, and a pattern whose regex fails to compile is dropped on its own.
+fn load_custom_patterns(path: &str) -> Vec<CustomPattern> {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        eprintln!("Unable to read --patterns file {path}");
+        return Vec::new();
+    };
+
+    let parsed: Result<PatternsFile, String> = if path.ends_with(".json") {
+        serde_json::from_str(&contents).map_err(|err| err.to_string())
+    } else {
+        toml::from_str(&contents).map_err(|err| err.to_string())
+    };
+    let parsed = match parsed {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("Failed to parse --patterns file {path}: {err}");
+            return Vec::new();
+        }
+    };
+
+    parsed
+        .patterns
+        .into_iter()
+        .filter_map(|def| match Regex::new(&def.regex) {
+            Ok(regex) => Some(CustomPattern {
+                regex,
+                log_type: def.log_type,
+            }),
+            Err(err) => {
+                eprintln!("Invalid regex in pattern {:?}: {err}", def.name);
+                None
+            }
+        })
+        .collect()
 }
 
 struct LogParser {
     basic_regex: Regex,
     http_regex: Regex,
     exception_regex: Regex,
+    custom_patterns: Vec<CustomPattern>,
 }
 
 impl LogParser {
-    fn new() -> Self {
+    fn new(patterns_file: Option<&str>) -> Self {
         Self {
             // Basic log format: [timestamp][severity][class][node_id] - just headers
             basic_regex: Regex::new(
@@ -84,10 +354,16 @@ impl LogParser {
             exception_regex: Regex::new(
                 r"(?P<exception_type>[a-zA-Z0-9_.]+(?:Exception|Error)): (?P<exception_message>[^\n]*)"
             ).unwrap(),
+            custom_patterns: patterns_file.map(load_custom_patterns).unwrap_or_default(),
         }
     }
 
     fn parse(&self, log_line: &str) -> Option<LogEntry> {
+        // User-supplied patterns take priority, in declaration order.
+        if let Some(entry) = self.parse_custom_log(log_line) {
+            return Some(entry);
+        }
+
         // Try HTTP format first (most specific)
         if let Some(entry) = self.parse_http_log(log_line) {
             return Some(entry);
@@ -102,6 +378,82 @@ impl LogParser {
         self.parse_basic_log(log_line)
     }
 
+    /// Try each `--patterns` entry in order. Named capture groups that match a known `LogEntry`
+    /// field populate it directly; anything else is collected into `extra`.
+    fn parse_custom_log(&self, log_line: &str) -> Option<LogEntry> {
+        for pattern in &self.custom_patterns {
+            let Some(caps) = pattern.regex.captures(log_line) else {
+                continue;
+            };
+
+            let mut entry = LogEntry {
+                sort_key: epoch_sentinel(),
+                timestamp: String::new(),
+                timestamp_rfc3339: String::new(),
+                log_type: pattern.log_type.clone(),
+                severity: String::new(),
+                class: String::new(),
+                node_id: String::new(),
+                body: String::new(),
+                request_method: None,
+                request_url: None,
+                request_parameters: None,
+                response_status: None,
+                response_status_code: None,
+                response_bytes: None,
+                response_latency_ms: None,
+                exception_type: None,
+                exception_message: None,
+                exception_trace: None,
+                extra: BTreeMap::new(),
+            };
+
+            for name in pattern.regex.capture_names().flatten() {
+                let Some(value) = caps.name(name) else {
+                    continue;
+                };
+                let value = value.as_str();
+                match name {
+                    "timestamp" => entry.timestamp = value.to_string(),
+                    "severity" => entry.severity = value.to_string(),
+                    "class" => entry.class = value.to_string(),
+                    "node_id" => entry.node_id = value.to_string(),
+                    "body" => entry.body = value.to_string(),
+                    "request_method" => entry.request_method = Some(value.to_string()),
+                    "request_url" => entry.request_url = Some(value.to_string()),
+                    "request_parameters" => entry.request_parameters = Some(value.to_string()),
+                    "response_status" => entry.response_status = Some(value.to_string()),
+                    "response_status_code" => entry.response_status_code = value.parse().ok(),
+                    "response_bytes" => entry.response_bytes = value.parse().ok(),
+                    "response_latency_ms" => entry.response_latency_ms = value.parse().ok(),
+                    "exception_type" => entry.exception_type = Some(value.to_string()),
+                    "exception_message" => entry.exception_message = Some(value.to_string()),
+                    "exception_trace" => entry.exception_trace = Some(value.to_string()),
+                    other => {
+                        entry.extra.insert(other.to_string(), value.to_string());
+                    }
+                }
+            }
+
+            return Some(entry);
+        }
+
+        None
+    }
+
+    /// Whether `line` begins a new log entry, used to decide when callers should flush the
+    /// accumulated multi-line buffer. Custom `--patterns` formats rarely share the built-in
+    /// `[timestamp][severity][class][node]` bracket shape they're meant to replace, so once any
+    /// are loaded, a line starts an entry if it matches one of them standalone instead of falling
+    /// back to the bracket heuristic.
+    fn is_entry_start(&self, line: &str) -> bool {
+        if self.custom_patterns.is_empty() {
+            is_log_line_start(line)
+        } else {
+            self.custom_patterns.iter().any(|pattern| pattern.regex.is_match(line))
+        }
+    }
+
     fn parse_http_log(&self, log_line: &str) -> Option<LogEntry> {
         let caps = self.http_regex.captures(log_line)?;
 
@@ -123,7 +475,9 @@ impl LogParser {
         };
 
         Some(LogEntry {
+            sort_key: epoch_sentinel(),
             timestamp: caps.name("timestamp")?.as_str().to_string(),
+            timestamp_rfc3339: String::new(),
             log_type: "http".to_string(),
             severity: caps.name("severity")?.as_str().trim().to_string(),
             class: caps.name("class")?.as_str().trim().to_string(),
@@ -149,6 +503,7 @@ impl LogParser {
             exception_type: None,
             exception_message: None,
             exception_trace: None,
+            extra: BTreeMap::new(),
         })
     }
 
@@ -177,7 +532,9 @@ impl LogParser {
         }
 
         Some(LogEntry {
+            sort_key: epoch_sentinel(),
             timestamp: caps.name("timestamp")?.as_str().to_string(),
+            timestamp_rfc3339: String::new(),
             log_type: "exception".to_string(),
             severity: caps.name("severity")?.as_str().trim().to_string(),
             class: caps.name("class")?.as_str().trim().to_string(),
@@ -193,6 +550,7 @@ impl LogParser {
             exception_type,
             exception_message,
             exception_trace,
+            extra: BTreeMap::new(),
         })
     }
 
@@ -208,7 +566,9 @@ impl LogParser {
         };
 
         Some(LogEntry {
+            sort_key: epoch_sentinel(),
             timestamp: caps.name("timestamp")?.as_str().to_string(),
+            timestamp_rfc3339: String::new(),
             log_type: "generic".to_string(),
             severity: caps.name("severity")?.as_str().trim().to_string(),
             class: caps.name("class")?.as_str().trim().to_string(),
@@ -224,6 +584,7 @@ impl LogParser {
             exception_type: None,
             exception_message: None,
             exception_trace: None,
+            extra: BTreeMap::new(),
         })
     }
 
@@ -289,17 +650,60 @@ fn is_log_line_start(s: &str) -> bool {
     s.starts_with('[') && s.len() > 11 && s.chars().nth(5) == Some('-')
 }
 
-/// Send the contents of the buffer to the channel (if present), clearing the buffer.
+/// Sentinel `sort_key` for the start of a stream, before any entry has parsed successfully.
+fn epoch_sentinel() -> DateTime<Utc> {
+    Utc.timestamp_opt(0, 0).single().unwrap()
+}
+
+/// Parse the OpenSearch node log timestamp format `YYYY-MM-DDTHH:MM:SS,mmm`, with an optional
+/// trailing `Z` or `+HH:MM`/`-HHMM` zone (assumed UTC when absent).
+fn parse_timestamp(raw: &str) -> Option<DateTime<Utc>> {
+    static TIMESTAMP_RE: OnceLock<Regex> = OnceLock::new();
+    let re = TIMESTAMP_RE.get_or_init(|| {
+        Regex::new(
+            r"^(?P<main>\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2},\d{3})(?P<zone>Z|[+-]\d{2}:?\d{2})?$",
+        )
+        .unwrap()
+    });
+    let caps = re.captures(raw.trim())?;
+
+    let zone = match caps.name("zone").map(|m| m.as_str()) {
+        None | Some("Z") => "+0000".to_string(),
+        Some(zone) => zone.replace(':', ""),
+    };
+    let full = format!("{}{zone}", caps["main"].replace(',', "."));
+    DateTime::parse_from_str(&full, "%Y-%m-%dT%H:%M:%S%.3f%z")
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Resolve `entry.sort_key`/`entry.timestamp_rfc3339` from `entry.timestamp`, inheriting
+/// `last_key` when the raw timestamp doesn't parse so an unparseable line sorts immediately next
+/// to the entry before it instead of snapping to the epoch sentinel.
+fn resolve_sort_key(entry: &mut LogEntry, last_key: &mut DateTime<Utc>) {
+    entry.sort_key = parse_timestamp(&entry.timestamp).unwrap_or(*last_key);
+    *last_key = entry.sort_key;
+    entry.timestamp_rfc3339 = entry.sort_key.to_rfc3339_opts(SecondsFormat::Millis, true);
+}
+
+/// Send the contents of the buffer to the channel (if present) when it passes `filters`,
+/// clearing the buffer. Filtering here, on the structured entry, means it behaves identically
+/// whether the eventual output is colored, JSON, ingested, or served over HTTP.
 fn send_buf(
     buf: &mut String,
-    tx: &SyncSender<String>,
+    tx: &SyncSender<LogEntry>,
     parser: &LogParser,
-    json_mode: bool,
-) -> Result<(), SendError<String>> {
+    filters: &Filters,
+    last_key: &mut DateTime<Utc>,
+) -> Result<(), SendError<()>> {
     if !buf.is_empty() {
-        if let Some(entry) = parser.parse(buf) {
-            if let Some(formatted) = format_entry(&entry, json_mode) {
-                tx.send(formatted)?;
+        if let Some(mut entry) = parser.parse(buf) {
+            resolve_sort_key(&mut entry, last_key);
+            if filters.matches(&entry) {
+                // `LogEntry` is large (a dozen+ fields plus the `extra` map); drop it rather than
+                // threading it back through the error so a closed receiver doesn't force a fat
+                // `Err` variant onto every caller of `send_buf`.
+                tx.send(entry).map_err(|_| SendError(()))?;
             }
         }
         buf.clear();
@@ -309,15 +713,16 @@ fn send_buf(
 
 fn send_lines(
     handle: File,
-    tx: SyncSender<String>,
+    tx: SyncSender<LogEntry>,
     parser: &LogParser,
-    json_mode: bool,
-) -> Result<(), SendError<String>> {
+    filters: &Filters,
+) -> Result<(), SendError<()>> {
     let mut buf = String::new();
+    let mut last_key = epoch_sentinel();
 
     for line in io::BufReader::new(handle).lines().map_while(Result::ok) {
-        if is_log_line_start(&line) {
-            send_buf(&mut buf, &tx, parser, json_mode)?;
+        if parser.is_entry_start(&line) {
+            send_buf(&mut buf, &tx, parser, filters, &mut last_key)?;
         }
         if !buf.is_empty() {
             buf.push('\n');
@@ -325,20 +730,20 @@ fn send_lines(
         buf.push_str(&line);
     }
 
-    send_buf(&mut buf, &tx, parser, json_mode)
+    send_buf(&mut buf, &tx, parser, filters, &mut last_key)
 }
 
 fn scan_log_lines<'s>(
     file: PathBuf,
     scope: &'s thread::Scope<'s, '_>,
     parser: &'s LogParser,
-    json_mode: bool,
+    filters: &'s Filters,
 ) -> LogReceiver {
     let (tx, rx) = sync_channel(CHAN_CAPACITY);
     scope.spawn(move || {
         match File::open(&file) {
             Ok(handle) => {
-                let _ = send_lines(handle, tx, parser, json_mode);
+                let _ = send_lines(handle, tx, parser, filters);
             }
             Err(err) => {
                 eprintln!("Unable to open {}: {err}", file.to_string_lossy());
@@ -352,11 +757,11 @@ fn collect_receivers<'s>(
     paths: Paths,
     scope: &'s thread::Scope<'s, '_>,
     parser: &'s LogParser,
-    json_mode: bool,
+    filters: &'s Filters,
 ) -> Vec<LogReceiver> {
     paths
         .filter_map(|res| match res {
-            Ok(file) => Some(scan_log_lines(file.to_path_buf(), scope, parser, json_mode)),
+            Ok(file) => Some(scan_log_lines(file.to_path_buf(), scope, parser, filters)),
             Err(err) => {
                 eprintln!("Unable to load path: {err}");
                 None
@@ -404,22 +809,55 @@ fn merge_receivers<'s>(
 fn main() {
     let args = Args::parse();
 
-    // Set color control based on flags
-    colored::control::set_override(!args.no_color && !args.json);
+    if let Some(Command::Serve { patterns, bind }) = args.command {
+        serve::run_serve(patterns, &bind);
+        return;
+    }
+
+    if args.patterns.is_empty() {
+        eprintln!("error: no file patterns specified (or use the `serve` subcommand)");
+        std::process::exit(1);
+    }
+
+    // Set color control based on flags. --ingest always serializes to JSON internally (the bulk
+    // body needs clean documents, not ANSI-colored lines), regardless of --json/--no-color.
+    let json_mode = args.json || args.ingest.is_some();
+    colored::control::set_override(!args.no_color && !json_mode);
 
-    let parser = LogParser::new();
+    let parser = LogParser::new(args.custom_patterns.as_deref());
+    let filters = Filters::from_args(&args);
 
     thread::scope(|s| {
-        let mut receivers = Vec::new();
-        for pattern in &args.patterns {
-            if let Ok(paths) = glob(pattern) {
-                receivers.extend(collect_receivers(paths, s, &parser, args.json));
+        let receivers = if args.follow {
+            vec![follow::run(&args.patterns, s, &parser, &filters)]
+        } else {
+            let mut receivers = Vec::new();
+            for pattern in &args.patterns {
+                if let Ok(paths) = glob(pattern) {
+                    receivers.extend(collect_receivers(paths, s, &parser, &filters));
+                }
             }
+            receivers
+        };
+
+        if let Some(endpoint) = &args.ingest {
+            let config = IngestConfig {
+                endpoint: endpoint.clone(),
+                index: args.index.clone(),
+                username: args.username.clone(),
+                password: args.password.clone(),
+                api_key: args.api_key.clone(),
+            };
+            ingest::run_ingest(merge_receivers(receivers, s), &config);
+            return;
         }
 
         let mut stdout = BufWriter::new(std::io::stdout());
         for entry in merge_receivers(receivers, s) {
-            if writeln!(stdout, "{entry}").is_err() {
+            let Some(formatted) = format_entry(&entry, json_mode) else {
+                continue;
+            };
+            if writeln!(stdout, "{formatted}").is_err() {
                 return;
             }
         }
@@ -432,7 +870,7 @@ mod tests {
 
     #[test]
     fn test_parse_http_request() {
-        let parser = LogParser::new();
+        let parser = LogParser::new(None);
         let log = "[2025-01-01T10:00:00,123][INFO ][o.o.n.c.logger           ][abc123node456] GET /_cluster/health local=true 200 OK 475 1";
 
         let entry = parser.parse(log).expect("Failed to parse HTTP log");
@@ -452,7 +890,7 @@ mod tests {
 
     #[test]
     fn test_parse_http_request_no_params() {
-        let parser = LogParser::new();
+        let parser = LogParser::new(None);
         let log = "[2025-01-01T10:00:00,123][INFO ][o.o.n.c.logger           ][abc123node456] GET / - 200 OK 578 1";
 
         let entry = parser.parse(log).expect("Failed to parse HTTP log");
@@ -465,7 +903,7 @@ mod tests {
 
     #[test]
     fn test_parse_exception_log() {
-        let parser = LogParser::new();
+        let parser = LogParser::new(None);
         let log = "[2025-01-01T10:00:00,123][ERROR][o.o.t.n.s.Transport      ][abc123node456] Exception during SSL: javax.net.ssl.SSLHandshakeException: Empty client certificate chain
 javax.net.ssl.SSLHandshakeException: Empty client certificate chain
 \tat java.base/sun.security.ssl.Alert.createSSLException(Alert.java:130)
@@ -487,7 +925,7 @@ javax.net.ssl.SSLHandshakeException: Empty client certificate chain
 
     #[test]
     fn test_parse_regular_log() {
-        let parser = LogParser::new();
+        let parser = LogParser::new(None);
         let log = "[2025-01-01T10:00:00,123][INFO ][c.a.a.c.MetricClient     ][abc123node456] flush is invoked with sync false";
 
         let entry = parser.parse(log).expect("Failed to parse regular log");
@@ -503,7 +941,7 @@ javax.net.ssl.SSLHandshakeException: Empty client certificate chain
 
     #[test]
     fn test_parse_multiline_exception() {
-        let parser = LogParser::new();
+        let parser = LogParser::new(None);
         let log = "[2025-01-01T10:00:00,123][WARN ][i.n.c.Handler            ][abc123node456] An exception was thrown
 io.netty.handler.codec.DecoderException: javax.net.ssl.SSLHandshakeException: Empty cert
 \tat io.netty.handler.codec.ByteToMessageDecoder.callDecode(ByteToMessageDecoder.java:500)
@@ -523,7 +961,7 @@ Caused by: javax.net.ssl.SSLHandshakeException: Empty cert
 
     #[test]
     fn test_parse_log_with_padded_fields() {
-        let parser = LogParser::new();
+        let parser = LogParser::new(None);
         let log = "[2025-01-01T10:00:00,123][INFO ][c.a.a.c.MetricClient     ][abc123node456] test message";
 
         let entry = parser.parse(log).expect("Failed to parse log");
@@ -534,6 +972,124 @@ Caused by: javax.net.ssl.SSLHandshakeException: Empty cert
         assert_eq!(entry.node_id, "abc123node456");
     }
 
+    fn parser_with_patterns(custom_patterns: Vec<CustomPattern>) -> LogParser {
+        LogParser {
+            custom_patterns,
+            ..LogParser::new(None)
+        }
+    }
+
+    #[test]
+    fn test_parse_custom_log_populates_known_fields() {
+        let pattern = CustomPattern {
+            regex: Regex::new(r"^(?P<timestamp>\S+) (?P<severity>\S+) (?P<body>.*)$").unwrap(),
+            log_type: "custom".to_string(),
+        };
+        let parser = parser_with_patterns(vec![pattern]);
+
+        let entry = parser
+            .parse("2025-01-01T10:00:00Z WARN disk usage high")
+            .expect("Failed to parse custom log");
+
+        assert_eq!(entry.log_type, "custom");
+        assert_eq!(entry.timestamp, "2025-01-01T10:00:00Z");
+        assert_eq!(entry.severity, "WARN");
+        assert_eq!(entry.body, "disk usage high");
+        assert!(entry.extra.is_empty());
+    }
+
+    #[test]
+    fn test_parse_custom_log_unknown_group_goes_to_extra() {
+        let pattern = CustomPattern {
+            regex: Regex::new(r"^(?P<timestamp>\S+) host=(?P<host>\S+) (?P<body>.*)$").unwrap(),
+            log_type: "custom".to_string(),
+        };
+        let parser = parser_with_patterns(vec![pattern]);
+
+        let entry = parser
+            .parse("2025-01-01T10:00:00Z host=db-1 connection refused")
+            .expect("Failed to parse custom log");
+
+        assert_eq!(entry.extra.get("host"), Some(&"db-1".to_string()));
+        assert_eq!(entry.body, "connection refused");
+    }
+
+    #[test]
+    fn test_parse_custom_log_takes_priority_over_builtin() {
+        let pattern = CustomPattern {
+            regex: Regex::new(
+                r"^\[(?P<timestamp>[^\]]+)\]\[(?P<severity>[^\]]+)\]\[(?P<class>[^\]]+)\]\[(?P<node_id>[^\]]+)\]\s*(?P<body>.*)$",
+            )
+            .unwrap(),
+            log_type: "custom".to_string(),
+        };
+        let parser = parser_with_patterns(vec![pattern]);
+        let log = "[2025-01-01T10:00:00,123][INFO ][c.a.a.c.MetricClient     ][abc123node456] flush is invoked with sync false";
+
+        let entry = parser.parse(log).expect("Failed to parse log");
+
+        // Without a custom pattern this falls through to the basic format (log_type "generic");
+        // with one declared, it should win even though the basic format would also match.
+        assert_eq!(entry.log_type, "custom");
+    }
+
+    #[test]
+    fn test_send_lines_splits_custom_format_entries() {
+        // Regression test: before `is_entry_start` was made pattern-aware, every physical line of
+        // a custom (non-bracket) format was glued into one buffer and only parsed at EOF, which
+        // silently dropped the whole file since the combined buffer never matched the pattern.
+        let pattern = CustomPattern {
+            regex: Regex::new(r"^(?P<timestamp>\S+) (?P<severity>\S+) (?P<body>.*)$").unwrap(),
+            log_type: "custom".to_string(),
+        };
+        let parser = parser_with_patterns(vec![pattern]);
+        let filters = Filters::default();
+
+        let path = std::env::temp_dir().join(format!(
+            "slog_test_send_lines_{}_custom.log",
+            std::process::id()
+        ));
+        std::fs::write(
+            &path,
+            "2025-01-01T10:00:00Z WARN disk usage high\n2025-01-01T10:00:01Z INFO disk usage normal\n",
+        )
+        .expect("Failed to write test log file");
+
+        let handle = File::open(&path).expect("Failed to open test log file");
+        let (tx, rx) = sync_channel(CHAN_CAPACITY);
+        send_lines(handle, tx, &parser, &filters).expect("send_lines failed");
+        std::fs::remove_file(&path).ok();
+
+        let entries: Vec<LogEntry> = rx.into_iter().collect();
+        assert_eq!(entries.len(), 2, "each custom-format line should be its own entry");
+        assert_eq!(entries[0].body, "disk usage high");
+        assert_eq!(entries[1].body, "disk usage normal");
+    }
+
+    #[test]
+    fn test_load_custom_patterns_skips_invalid_regex() {
+        let path = std::env::temp_dir().join(format!(
+            "slog_test_patterns_{}_skip_invalid.json",
+            std::process::id()
+        ));
+        std::fs::write(
+            &path,
+            r#"{
+                "patterns": [
+                    {"name": "broken", "regex": "(unclosed", "log_type": "broken"},
+                    {"name": "ok", "regex": "^(?P<timestamp>\\S+) (?P<body>.*)$", "log_type": "ok"}
+                ]
+            }"#,
+        )
+        .expect("Failed to write test patterns file");
+
+        let patterns = load_custom_patterns(path.to_str().unwrap());
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(patterns.len(), 1);
+        assert_eq!(patterns[0].log_type, "ok");
+    }
+
     #[test]
     fn test_is_log_line_start() {
         assert!(is_log_line_start("[2025-01-01T10:00:00,123][INFO ][test][node] message"));
@@ -545,29 +1101,86 @@ Caused by: javax.net.ssl.SSLHandshakeException: Empty cert
 
     #[test]
     fn test_json_serialization() {
-        let parser = LogParser::new();
+        let parser = LogParser::new(None);
         let log = "[2025-01-01T10:00:00,123][INFO ][o.o.n.c.logger           ][abc123node456] GET / - 200 OK 100 5";
 
-        let entry = parser.parse(log).expect("Failed to parse log");
+        let mut entry = parser.parse(log).expect("Failed to parse log");
+        resolve_sort_key(&mut entry, &mut epoch_sentinel());
         let json = serde_json::to_string(&entry).expect("Failed to serialize to JSON");
 
-        // Verify JSON contains expected fields with @timestamp
-        assert!(json.contains("\"@timestamp\":\"2025-01-01T10:00:00,123\""));
+        // Verify JSON contains expected fields, with @timestamp normalized to RFC3339
+        assert!(json.contains("\"@timestamp\":\"2025-01-01T10:00:00.123Z\""));
         assert!(json.contains("\"severity\":\"INFO\""));
         assert!(json.contains("\"request_method\":\"GET\""));
         assert!(json.contains("\"response_status_code\":200"));
 
         // Verify optional fields are excluded when None
         let regular_log = "[2025-01-01T10:00:00,123][INFO ][test][node] message";
-        let regular_entry = parser.parse(regular_log).expect("Failed to parse");
+        let mut regular_entry = parser.parse(regular_log).expect("Failed to parse");
+        resolve_sort_key(&mut regular_entry, &mut epoch_sentinel());
         let regular_json = serde_json::to_string(&regular_entry).expect("Failed to serialize");
         assert!(!regular_json.contains("request_method"));
         assert!(!regular_json.contains("exception_type"));
     }
 
+    #[test]
+    fn test_parse_timestamp_formats() {
+        let naive = parse_timestamp("2025-01-01T10:00:00,123").expect("naive timestamp");
+        let zulu = parse_timestamp("2025-01-01T10:00:00,123Z").expect("Z timestamp");
+        let offset = parse_timestamp("2025-01-01T12:00:00,123+02:00").expect("offset timestamp");
+
+        assert_eq!(naive, zulu);
+        assert_eq!(naive, offset);
+        assert!(parse_timestamp("not a timestamp").is_none());
+    }
+
+    #[test]
+    fn test_resolve_sort_key_inherits_on_unparseable() {
+        let mut last_key = epoch_sentinel();
+
+        let mut good = LogEntry {
+            timestamp: "2025-01-01T10:00:00,123".to_string(),
+            ..blank_entry()
+        };
+        resolve_sort_key(&mut good, &mut last_key);
+        assert_eq!(good.timestamp_rfc3339, "2025-01-01T10:00:00.123Z");
+
+        let mut garbled = LogEntry {
+            timestamp: "not a timestamp".to_string(),
+            ..blank_entry()
+        };
+        resolve_sort_key(&mut garbled, &mut last_key);
+        assert_eq!(garbled.sort_key, good.sort_key);
+        assert_eq!(garbled.timestamp_rfc3339, good.timestamp_rfc3339);
+    }
+
+    fn blank_entry() -> LogEntry {
+        LogEntry {
+            sort_key: epoch_sentinel(),
+            timestamp: String::new(),
+            timestamp_rfc3339: String::new(),
+            log_type: "generic".to_string(),
+            severity: String::new(),
+            class: String::new(),
+            node_id: String::new(),
+            body: String::new(),
+            request_method: None,
+            request_url: None,
+            request_parameters: None,
+            response_status: None,
+            response_status_code: None,
+            response_bytes: None,
+            response_latency_ms: None,
+            exception_type: None,
+            exception_message: None,
+            exception_trace: None,
+            extra: BTreeMap::new(),
+        }
+    }
+
     #[test]
     fn test_log_type_discriminator() {
-        let parser = LogParser::new();
+        let parser = LogParser::new(None);
 
         // Test HTTP log type
         let http_log = "[2025-01-01T10:00:00,123][INFO ][o.o.n.c.logger][node] GET / - 200 OK 100 5";
@@ -590,7 +1203,7 @@ Caused by: javax.net.ssl.SSLHandshakeException: Empty cert
 
     #[test]
     fn test_extract_exception_details_with_caused_by() {
-        let parser = LogParser::new();
+        let parser = LogParser::new(None);
         let body = "Exception occurred: java.lang.RuntimeException: Test error
 java.lang.RuntimeException: Test error
 \tat com.example.Test.method(Test.java:10)
@@ -0,0 +1,210 @@
+use std::io::{self, Read};
+use std::thread;
+
+use glob::{Pattern, glob};
+use tiny_http::{Header, Response, Server, StatusCode};
+
+use crate::{Filters, LogEntry, LogParser, LogReceiver, collect_receivers, merge_receivers};
+
+/// Query parameters accepted on `GET /logs`, each mapping onto a `LogEntry` field.
+#[derive(Default)]
+struct QueryFilters {
+    severity: Option<String>,
+    log_type: Option<String>,
+    class: Option<Pattern>,
+    node: Option<String>,
+    /// Compared against the emitted `@timestamp` (RFC3339), since that's the only timestamp form
+    /// a client of this API ever sees.
+    since: Option<String>,
+    until: Option<String>,
+    status_gte: Option<u16>,
+}
+
+/// Decode a `application/x-www-form-urlencoded` query value: `+` becomes a space and `%XX`
+/// becomes the byte it encodes. A malformed escape is kept as literal text rather than failing
+/// the whole request; if the decoded bytes aren't valid UTF-8, the raw input is returned.
+fn decode_query_value(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => {
+                out.push(b' ');
+                i += 1;
+            }
+            b'%' => {
+                // `get` (rather than slicing) avoids panicking when `i + 1`/`i + 3` fall inside
+                // a multi-byte UTF-8 character; `is_ascii` then rejects any such non-hex slice.
+                let hex = value
+                    .get(i + 1..i + 3)
+                    .filter(|s| s.is_ascii())
+                    .and_then(|s| u8::from_str_radix(s, 16).ok());
+                match hex {
+                    Some(byte) => {
+                        out.push(byte);
+                        i += 3;
+                    }
+                    None => {
+                        out.push(bytes[i]);
+                        i += 1;
+                    }
+                }
+            }
+            byte => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(out).unwrap_or_else(|_| value.to_string())
+}
+
+fn parse_query(url: &str) -> QueryFilters {
+    let mut filters = QueryFilters::default();
+    let Some(query) = url.split_once('?').map(|(_, q)| q) else {
+        return filters;
+    };
+
+    for pair in query.split('&') {
+        let Some((key, value)) = pair.split_once('=') else {
+            continue;
+        };
+        let value = decode_query_value(value);
+        match key {
+            "severity" => filters.severity = Some(value),
+            "log_type" => filters.log_type = Some(value),
+            "class" => filters.class = Pattern::new(&value).ok(),
+            "node" => filters.node = Some(value),
+            "since" => filters.since = Some(value),
+            "until" => filters.until = Some(value),
+            "status_gte" => filters.status_gte = value.parse().ok(),
+            _ => {}
+        }
+    }
+
+    filters
+}
+
+fn entry_matches(entry: &LogEntry, filters: &QueryFilters) -> bool {
+    if let Some(severity) = &filters.severity {
+        if &entry.severity != severity {
+            return false;
+        }
+    }
+    if let Some(log_type) = &filters.log_type {
+        if &entry.log_type != log_type {
+            return false;
+        }
+    }
+    if let Some(class) = &filters.class {
+        if !class.matches(&entry.class) {
+            return false;
+        }
+    }
+    if let Some(node) = &filters.node {
+        if &entry.node_id != node {
+            return false;
+        }
+    }
+    if let Some(since) = &filters.since {
+        if &entry.timestamp_rfc3339 < since {
+            return false;
+        }
+    }
+    if let Some(until) = &filters.until {
+        if &entry.timestamp_rfc3339 > until {
+            return false;
+        }
+    }
+    if let Some(status_gte) = filters.status_gte {
+        if entry.response_status_code.unwrap_or(0) < status_gte {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Streams NDJSON lines lazily from a merged `LogReceiver`, so `GET /logs` never materializes
+/// the whole merged stream before writing a response.
+struct LogStreamReader {
+    receiver: LogReceiver,
+    filters: QueryFilters,
+    pending: Vec<u8>,
+}
+
+impl Read for LogStreamReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pending.is_empty() {
+            match self.receiver.next() {
+                Some(entry) => {
+                    if entry_matches(&entry, &self.filters) {
+                        if let Ok(json) = serde_json::to_string(&entry) {
+                            self.pending.extend_from_slice(json.as_bytes());
+                            self.pending.push(b'\n');
+                        }
+                    }
+                }
+                None => return Ok(0),
+            }
+        }
+
+        let n = buf.len().min(self.pending.len());
+        buf[..n].copy_from_slice(&self.pending[..n]);
+        self.pending.drain(..n);
+        Ok(n)
+    }
+}
+
+/// Serve the merged stream of `patterns` at `GET /logs` on `bind`, re-scanning the patterns for
+/// each incoming request so a long-lived server always reflects the current file contents.
+pub fn run_serve(patterns: Vec<String>, bind: &str) {
+    let server = match Server::http(bind) {
+        Ok(server) => server,
+        Err(err) => {
+            eprintln!("Failed to bind {bind}: {err}");
+            return;
+        }
+    };
+    eprintln!("Listening on http://{bind}/logs");
+
+    let parser = LogParser::new(None);
+    let no_filters = Filters::default();
+    thread::scope(|s| {
+        for request in server.incoming_requests() {
+            // `LogStreamReader::read` blocks until the merge yields the next entry, so responding
+            // inline here would let
+            // one slow/live client starve every other connection. Each request gets its own
+            // scoped thread so concurrent `GET /logs` streams don't queue behind each other.
+            s.spawn(|| {
+                let path = request.url().split('?').next().unwrap_or("");
+                if path != "/logs" {
+                    let _ = request.respond(Response::empty(StatusCode(404)));
+                    return;
+                }
+
+                let filters = parse_query(request.url());
+                let mut receivers = Vec::new();
+                for pattern in &patterns {
+                    if let Ok(paths) = glob(pattern) {
+                        // Query-param filtering happens in `entry_matches` below, on the merged
+                        // stream, so every file is scanned unfiltered here.
+                        receivers.extend(collect_receivers(paths, s, &parser, &no_filters));
+                    }
+                }
+                let reader = LogStreamReader {
+                    receiver: merge_receivers(receivers, s),
+                    filters,
+                    pending: Vec::new(),
+                };
+
+                let content_type =
+                    Header::from_bytes(&b"Content-Type"[..], &b"application/x-ndjson"[..]).unwrap();
+                let response =
+                    Response::new(StatusCode(200), vec![content_type], reader, None, None);
+                let _ = request.respond(response);
+            });
+        }
+    });
+}